@@ -0,0 +1,76 @@
+use crate::neptunclass::ParseError;
+use chrono::{NaiveDate, TimeDelta};
+use icalendar::{Component, DatePerhapsTime, Event};
+use std::fmt;
+
+/// A date-only event such as a holiday, an exam period or a registration deadline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllDayEvent {
+    pub summary: String,
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+    uid: Option<String>,
+}
+
+impl AllDayEvent {
+    pub fn is_all_day(event: &Event) -> bool {
+        matches!(event.get_start(), Some(DatePerhapsTime::Date(_)))
+    }
+
+    pub fn uid(&self) -> Option<&str> {
+        self.uid.as_deref()
+    }
+
+    pub fn covers(&self, date: &NaiveDate) -> bool {
+        self.first_day <= *date && *date <= self.last_day
+    }
+
+    pub fn overlaps(&self, first_day: &NaiveDate, last_day: &NaiveDate) -> bool {
+        self.first_day <= *last_day && *first_day <= self.last_day
+    }
+}
+
+impl TryFrom<&Event> for AllDayEvent {
+    type Error = ParseError;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        let summary = event
+            .get_summary()
+            .ok_or(ParseError::MissingField("SUMMARY"))?;
+        let first_day = match event.get_start() {
+            Some(DatePerhapsTime::Date(date)) => date,
+            Some(_) => return Err(ParseError::BadDate("DTSTART")),
+            None => return Err(ParseError::MissingField("DTSTART")),
+        };
+        // DTEND of a date-only event is exclusive, a missing one means a single day.
+        let last_day = match event.get_end() {
+            Some(DatePerhapsTime::Date(date)) => date - TimeDelta::days(1),
+            Some(end) => end.date_naive(),
+            None => first_day,
+        }
+        .max(first_day);
+        Ok(AllDayEvent {
+            summary: summary.to_owned(),
+            first_day,
+            last_day,
+            uid: event.get_uid().map(|s| s.to_owned()),
+        })
+    }
+}
+
+impl fmt::Display for AllDayEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.first_day == self.last_day {
+            write!(f, "{} ({})", self.summary, self.first_day.format("%m.%d."))
+        } else {
+            write!(
+                f,
+                "{} ({} - {})",
+                self.summary,
+                self.first_day.format("%m.%d."),
+                self.last_day.format("%m.%d.")
+            )
+        }
+    }
+}
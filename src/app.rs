@@ -0,0 +1,1568 @@
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use neptunics::timetable::{TimeTable, TimeTableNavigation, TimeTableState};
+use ratatui::style::palette::tailwind;
+use ratatui::{
+    layout::{Constraint, Layout, Margin, Rect},
+    prelude::Direction,
+    style::{Color, Modifier, Style, Stylize},
+    text::Text,
+    widgets::{
+        Block, BorderType, Cell, HighlightSpacing, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, TableState, Wrap,
+    },
+    DefaultTerminal, Frame,
+};
+use std::io::Result;
+
+use icalendar::{Calendar, EventStatus};
+use neptunics::calendar::{
+    get_classes, merge, parse_calendar, CalendarError, ParsedCalendar, SkippedEvent,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use ratatui_explorer::FileExplorer;
+
+use crate::cli::View;
+use crate::display_timezone;
+use chrono_tz::Tz;
+use neptunics::config::{Config, ConfigError};
+use neptunics::coursecode::hours_by_type;
+use neptunics::eventfilter::EventFilter;
+use neptunics::exportlanguage::ExportLanguage;
+use neptunics::freetime::free_time;
+use neptunics::location::group_by_building;
+use neptunics::neptunclass::NeptunClass;
+use neptunics::neptuncourse::NeptunCourse;
+use neptunics::neptunevent::{EventKind, NeptunEvent};
+use neptunics::parserules::ParseRules;
+use neptunics::schedule::{
+    get_all_day_events_by_day, get_all_day_events_by_week, get_classes_by_day, get_classes_by_week,
+    week_bounds, weekday_name,
+};
+use neptunics::teacher::{group_by_teacher, join_names, ContactBook};
+
+const ITEM_HEIGHT: usize = 4;
+const MAIN_INFO_TEXT: [&str; 2] = [
+    "(Esc) kilépés | (↑) lépés felfelé | (↓) lépés lefelé | (←) előző nap | (→) következő nap",
+    "(Enter) részletek | (1-5) eseménytípusok | (t) óratípus | (b) épület | (c) elmaradt | (v) vizsgák | (o) oktatók | (s) források | (d) diagnosztika",
+];
+const FILE_NOT_FOUND_INFO_TEXT: &str =
+    "(Esc) kilépés | (Enter) Új fájl kiválasztása | (d) diagnosztika";
+const DIAGNOSTICS_INFO_TEXT: [&str; 2] = [
+    "(Esc) kilépés | (↑) lépés felfelé | (↓) lépés lefelé ",
+    "(d) vissza | (Enter) Új fájl kiválasztása",
+];
+const EXAM_LIST_INFO_TEXT: [&str; 2] = [
+    "(Esc) kilépés | (↑) lépés felfelé | (↓) lépés lefelé ",
+    "(v) vissza | (d) diagnosztika",
+];
+const TEACHER_LIST_INFO_TEXT: [&str; 2] = [
+    "(Esc) kilépés | (↑) lépés felfelé | (↓) lépés lefelé ",
+    "(o) vissza | (d) diagnosztika",
+];
+const SOURCE_LIST_INFO_TEXT: [&str; 2] = [
+    "(Esc) kilépés | (↑) lépés felfelé | (↓) lépés lefelé ",
+    "(Enter) megjelenítés ki/be | (a) új naptár hozzáadása | (s) vissza | (d) diagnosztika",
+];
+const DETAIL_INFO_TEXT: &str = "(Esc) kilépés | (Enter) vissza | (d) diagnosztika";
+const FILE_SELECT_INFO_TEXT: [&str; 2] = [
+    "(Esc) kilépés | (↑) lépés felfelé | (↓) lépés lefelé ",
+    "(Enter) könyvtár: belépés | (Enter) fájl: kiválasztás",
+];
+const LONGEST_ITEMS_LENS: (u16, u16, u16, u16, u16) = (25, 20, 13, 17, 25);
+
+#[derive(Clone, Copy)]
+enum CurrentScreen {
+    FileSelect,
+    FileNotFound,
+    DailyView,
+    TimeTableView,
+    ExamList,
+    Diagnostics,
+    Detail,
+    TeacherList,
+    SourceList,
+}
+
+pub struct App {
+    tablestate: TableState,
+    timetablestate: TimeTableState,
+    sources: Vec<(String, ParsedCalendar)>,
+    events: Vec<NeptunEvent>,
+    language: ExportLanguage,
+    filter: EventFilter,
+    selected_classes: usize,
+    longest_items_lens: (u16, u16, u16, u16, u16), // name, code, duration, location, teachers
+    scroll_state: ScrollbarState,
+    colors: TableColors,
+    selected_date: NaiveDate,
+    timezone: Tz,
+    current_screen: CurrentScreen,
+    file_explorer: FileExplorer,
+    skipped_events: Vec<SkippedEvent>,
+    load_error: Option<CalendarError>,
+    rules: ParseRules,
+    config_error: Option<ConfigError>,
+    diagnostics_state: TableState,
+    exam_tablestate: TableState,
+    detail_event: Option<usize>,
+    detail_return: CurrentScreen,
+    contacts: ContactBook,
+    teacher_tablestate: TableState,
+    source_tablestate: TableState,
+}
+
+impl App {
+    pub fn new(
+        calendars: Vec<(String, std::result::Result<Calendar, CalendarError>)>,
+        config: std::result::Result<Config, ConfigError>,
+        date: Option<NaiveDate>,
+        view: View,
+        filter: EventFilter,
+    ) -> Self {
+        let (config, config_error) = match config {
+            Ok(config) => (config, None),
+            Err(err) => (Config::default(), Some(err)),
+        };
+        let (contacts, contacts_error) = match config.contacts_path() {
+            Some(path) if path.exists() => match ContactBook::load(&path.to_string_lossy()) {
+                Ok(contacts) => (contacts, None),
+                Err(err) => (ContactBook::default(), Some(err)),
+            },
+            _ => (ContactBook::default(), None),
+        };
+        let timezone = display_timezone(config.timezone);
+        let today: NaiveDate =
+            date.unwrap_or_else(|| chrono::Utc::now().with_timezone(&timezone).date_naive());
+        // let today: NaiveDate = NaiveDate::from_ymd_opt(2024, 11, 20).unwrap();
+        let file_explorer_theme = ratatui_explorer::Theme::default().add_default_title();
+        let mut app = Self {
+            tablestate: TableState::default().with_selected(0),
+            timetablestate: TimeTableState::default(),
+            sources: Vec::new(),
+            events: Vec::new(),
+            language: ExportLanguage::default(),
+            filter,
+            longest_items_lens: LONGEST_ITEMS_LENS,
+            scroll_state: ScrollbarState::new(0),
+            selected_classes: 0,
+            colors: TableColors::new(),
+            selected_date: today,
+            timezone,
+            current_screen: CurrentScreen::FileSelect,
+            file_explorer: FileExplorer::with_theme(file_explorer_theme).unwrap(),
+            skipped_events: Vec::new(),
+            load_error: None,
+            rules: config.parse_rules(),
+            config_error: config_error.or(contacts_error),
+            diagnostics_state: TableState::default(),
+            exam_tablestate: TableState::default(),
+            detail_event: None,
+            detail_return: CurrentScreen::TimeTableView,
+            contacts,
+            teacher_tablestate: TableState::default().with_selected(0),
+            source_tablestate: TableState::default().with_selected(0),
+        };
+        let mut loaded = false;
+        for (path, calendar) in calendars {
+            loaded |= app.load_calendar(&path, calendar);
+        }
+        if loaded {
+            app.current_screen = match view {
+                View::Daily => CurrentScreen::DailyView,
+                View::Week => CurrentScreen::TimeTableView,
+            };
+        }
+        app
+    }
+
+    /// Adds the calendar read from `path` to the loaded ones, replacing the one loaded from the
+    /// same path before.
+    fn load_calendar(
+        &mut self,
+        path: &str,
+        calendar: std::result::Result<Calendar, CalendarError>,
+    ) -> bool {
+        self.diagnostics_state = TableState::default();
+        match calendar {
+            Ok(cal) => {
+                let parsed = get_classes(&cal, &self.rules).with_source(path);
+                match self.sources.iter_mut().find(|(source, _)| source == path) {
+                    Some((_, loaded)) => *loaded = parsed,
+                    None => self.sources.push((path.to_owned(), parsed)),
+                }
+                let merged = merge(self.sources.iter().map(|(_, parsed)| parsed.clone()));
+                self.events = merged.events;
+                self.language = merged.language;
+                self.skipped_events = merged.skipped;
+                self.detail_event = None;
+                self.load_error = None;
+                true
+            }
+            Err(err) => {
+                self.load_error = Some(err);
+                false
+            }
+        }
+    }
+
+    fn index_of_ongoing(&self, selected_classes: &[&NeptunEvent]) -> Option<usize> {
+        let now = chrono::Utc::now();
+        selected_classes.iter().position(|class| {
+            class
+                .span()
+                .is_some_and(|(start, end)| start <= now && end >= now)
+        })
+    }
+
+    fn update_filter(&mut self, update: impl FnOnce(&mut EventFilter)) {
+        update(&mut self.filter);
+        self.tablestate.select(Some(0));
+        self.timetablestate = TimeTableState::default();
+    }
+
+    /// Every building a class is held in, for the building filter to step through.
+    fn buildings(&self) -> Vec<String> {
+        group_by_building(self.events.iter().filter_map(NeptunEvent::class))
+            .into_iter()
+            .map(|(building, _)| building)
+            .collect()
+    }
+
+    /// Every session of the class's course, counting only events of the same kind.
+    fn course_of(&self, event: &NeptunEvent) -> Option<NeptunCourse> {
+        let class = event.class()?;
+        let sessions = self
+            .events
+            .iter()
+            .filter(|other| other.kind() == event.kind())
+            .filter_map(NeptunEvent::class)
+            .filter(|other| other.code() == class.code());
+        NeptunCourse::group(sessions).into_iter().next()
+    }
+
+    fn is_room_change(&self, event: &NeptunEvent) -> bool {
+        match (event.class(), self.course_of(event)) {
+            (Some(class), Some(course)) => course.is_room_change(class),
+            _ => false,
+        }
+    }
+
+    fn kind_filter_title(&self) -> String {
+        EventKind::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| {
+                let mark = if self.filter.is_hidden(*kind) {
+                    "□"
+                } else {
+                    "■"
+                };
+                format!("({}) {} {}", i + 1, mark, kind.label())
+            })
+            .chain([
+                format!(
+                    "(t) {}",
+                    self.filter
+                        .class_type()
+                        .map_or("minden óratípus", |class_type| class_type.label())
+                ),
+                format!("(b) {}", self.filter.building().unwrap_or("minden épület")),
+                format!(
+                    "(c) {} elmaradt",
+                    if self.filter.hides_cancelled() {
+                        "□"
+                    } else {
+                        "■"
+                    }
+                ),
+            ])
+            .collect::<Vec<String>>()
+            .join("  ")
+    }
+
+    fn truncate_string(&self, str: &String, index: usize) -> String {
+        let len = match index {
+            0 => self.longest_items_lens.0,
+            1 => self.longest_items_lens.1,
+            2 => self.longest_items_lens.2,
+            3 => self.longest_items_lens.3,
+            4 => self.longest_items_lens.4,
+            _ => 0,
+        };
+        if str.graphemes(true).count() > len as usize {
+            let mut trunc_str: String = str.graphemes(true).take(len as usize).collect();
+            trunc_str.push_str("...");
+            trunc_str
+        } else {
+            str.to_string()
+        }
+    }
+
+    fn next_day(&mut self) {
+        self.selected_date += TimeDelta::days(1);
+    }
+
+    fn prev_day(&mut self) {
+        self.selected_date -= TimeDelta::days(1);
+    }
+
+    pub fn next_row(&mut self) {
+        let i = match self.tablestate.selected() {
+            Some(i) => {
+                if i >= self.selected_classes.saturating_sub(1) {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            _ => 0,
+        };
+        self.tablestate.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+    }
+
+    pub fn prev_row(&mut self) {
+        let i = match self.tablestate.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.selected_classes.saturating_sub(1)
+                } else {
+                    i - 1
+                }
+            }
+            _ => 0,
+        };
+        self.tablestate.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+    }
+
+    fn try_to_parse_calendar(&mut self) {
+        let path = self
+            .file_explorer
+            .current()
+            .path()
+            .as_path()
+            .display()
+            .to_string();
+        self.current_screen = if self.load_calendar(&path, parse_calendar(path.as_str())) {
+            CurrentScreen::TimeTableView
+        } else {
+            CurrentScreen::FileNotFound
+        };
+    }
+
+    fn toggle_diagnostics(&mut self) {
+        self.current_screen = match self.current_screen {
+            CurrentScreen::DailyView
+            | CurrentScreen::TimeTableView
+            | CurrentScreen::ExamList
+            | CurrentScreen::Detail
+            | CurrentScreen::TeacherList
+            | CurrentScreen::SourceList
+            | CurrentScreen::FileNotFound => CurrentScreen::Diagnostics,
+            CurrentScreen::Diagnostics if self.load_error.is_some() => CurrentScreen::FileNotFound,
+            CurrentScreen::Diagnostics => CurrentScreen::TimeTableView,
+            CurrentScreen::FileSelect => return,
+        };
+    }
+
+    /// Opens the detail view of the event selected in the daily table or the timetable.
+    fn open_detail(&mut self) {
+        let selected = match self.current_screen {
+            CurrentScreen::DailyView => get_classes_by_day(
+                &self.events,
+                &self.selected_date,
+                &self.timezone,
+                &self.filter,
+            )
+            .get(self.tablestate.selected().unwrap_or_default())
+            .copied(),
+            CurrentScreen::TimeTableView => self.timetablestate.index().and_then(|i| {
+                get_classes_by_week(
+                    &self.events,
+                    &self.selected_date,
+                    &self.timezone,
+                    &self.filter,
+                )
+                .get(i)
+                .copied()
+            }),
+            _ => None,
+        };
+        let Some(selected) = selected else {
+            return;
+        };
+        self.detail_event = self
+            .events
+            .iter()
+            .position(|event| std::ptr::eq(event, selected));
+        self.detail_return = self.current_screen;
+        self.current_screen = CurrentScreen::Detail;
+    }
+
+    fn toggle_teacher_list(&mut self) {
+        self.current_screen = match self.current_screen {
+            CurrentScreen::DailyView | CurrentScreen::TimeTableView => CurrentScreen::TeacherList,
+            CurrentScreen::TeacherList => CurrentScreen::TimeTableView,
+            _ => return,
+        };
+    }
+
+    fn toggle_source_list(&mut self) {
+        self.current_screen = match self.current_screen {
+            CurrentScreen::DailyView | CurrentScreen::TimeTableView => CurrentScreen::SourceList,
+            CurrentScreen::SourceList => CurrentScreen::TimeTableView,
+            _ => return,
+        };
+    }
+
+    fn toggle_selected_source(&mut self) {
+        let Some((source, _)) = self
+            .source_tablestate
+            .selected()
+            .and_then(|i| self.sources.get(i))
+        else {
+            return;
+        };
+        let source = source.clone();
+        self.update_filter(|filter| filter.toggle_source(&source));
+    }
+
+    fn toggle_exam_list(&mut self) {
+        self.current_screen = match self.current_screen {
+            CurrentScreen::DailyView | CurrentScreen::TimeTableView => CurrentScreen::ExamList,
+            CurrentScreen::ExamList => CurrentScreen::TimeTableView,
+            _ => return,
+        };
+    }
+
+    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let event = event::read()?;
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Tab => match self.current_screen {
+                            CurrentScreen::DailyView => {
+                                self.current_screen = CurrentScreen::TimeTableView
+                            }
+                            CurrentScreen::TimeTableView => {
+                                self.current_screen = CurrentScreen::DailyView
+                            }
+                            _ => {}
+                        },
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Char('d') => {
+                            self.toggle_diagnostics();
+                            continue;
+                        }
+                        KeyCode::Char('v') => {
+                            self.toggle_exam_list();
+                            continue;
+                        }
+                        KeyCode::Char('o') => {
+                            self.toggle_teacher_list();
+                            continue;
+                        }
+                        KeyCode::Char('s') => {
+                            self.toggle_source_list();
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    match self.current_screen {
+                        // let shift_pressed: bool = key.modifiers.contains(KeyModifiers::SHIFT);
+                        CurrentScreen::DailyView | CurrentScreen::TimeTableView
+                            if matches!(key.code, KeyCode::Char('1'..='5')) =>
+                        {
+                            if let KeyCode::Char(c) = key.code {
+                                let kind = EventKind::ALL[c as usize - '1' as usize];
+                                self.update_filter(|filter| filter.toggle_kind(kind));
+                            }
+                        }
+                        CurrentScreen::DailyView | CurrentScreen::TimeTableView
+                            if key.code == KeyCode::Char('t') =>
+                        {
+                            self.update_filter(EventFilter::cycle_class_type)
+                        }
+                        CurrentScreen::DailyView | CurrentScreen::TimeTableView
+                            if key.code == KeyCode::Char('b') =>
+                        {
+                            let buildings = self.buildings();
+                            self.update_filter(|filter| filter.cycle_building(&buildings))
+                        }
+                        CurrentScreen::DailyView | CurrentScreen::TimeTableView
+                            if key.code == KeyCode::Char('c') =>
+                        {
+                            self.update_filter(EventFilter::toggle_cancelled)
+                        }
+                        CurrentScreen::DailyView | CurrentScreen::TimeTableView
+                            if key.code == KeyCode::Enter =>
+                        {
+                            self.open_detail()
+                        }
+                        CurrentScreen::DailyView => match key.code {
+                            // KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                            KeyCode::Char('j') | KeyCode::Down => self.next_row(),
+                            KeyCode::Char('k') | KeyCode::Up => self.prev_row(),
+                            KeyCode::Char('h') | KeyCode::Left => self.prev_day(),
+                            KeyCode::Char('l') | KeyCode::Right => self.next_day(),
+                            _ => {}
+                        },
+                        CurrentScreen::TimeTableView => match key.code {
+                            // KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                self.timetablestate.navigate(TimeTableNavigation::Down)
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                self.timetablestate.navigate(TimeTableNavigation::Up)
+                            }
+                            KeyCode::Char('h') | KeyCode::Left => {
+                                self.timetablestate.navigate(TimeTableNavigation::Left)
+                            }
+                            KeyCode::Char('l') | KeyCode::Right => {
+                                self.timetablestate.navigate(TimeTableNavigation::Right)
+                            }
+                            _ => {}
+                        },
+                        CurrentScreen::FileSelect => match key.code {
+                            // KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                            KeyCode::Enter => {
+                                if self.file_explorer.current().is_dir() {
+                                    self.file_explorer.handle(&event)?;
+                                } else {
+                                    self.try_to_parse_calendar();
+                                }
+                            }
+                            _ => self.file_explorer.handle(&event)?,
+                        },
+                        CurrentScreen::FileNotFound => {
+                            if key.code == KeyCode::Enter {
+                                self.current_screen = CurrentScreen::FileSelect
+                            }
+                        }
+                        CurrentScreen::ExamList => match key.code {
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                self.exam_tablestate.select_next()
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                self.exam_tablestate.select_previous()
+                            }
+                            _ => {}
+                        },
+                        CurrentScreen::Diagnostics => match key.code {
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                self.diagnostics_state.select_next()
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                self.diagnostics_state.select_previous()
+                            }
+                            KeyCode::Enter => self.current_screen = CurrentScreen::FileSelect,
+                            _ => {}
+                        },
+                        CurrentScreen::TeacherList => match key.code {
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                self.teacher_tablestate.select_next()
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                self.teacher_tablestate.select_previous()
+                            }
+                            _ => {}
+                        },
+                        CurrentScreen::SourceList => match key.code {
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                self.source_tablestate.select_next()
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                self.source_tablestate.select_previous()
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') => self.toggle_selected_source(),
+                            KeyCode::Char('a') => self.current_screen = CurrentScreen::FileSelect,
+                            _ => {}
+                        },
+                        CurrentScreen::Detail => {
+                            if matches!(key.code, KeyCode::Enter | KeyCode::Backspace) {
+                                self.current_screen = self.detail_return
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        match self.current_screen {
+            CurrentScreen::FileNotFound => {
+                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
+                let rects = vertical.split(frame.area());
+                self.render_file_not_found(frame, rects[0]);
+                self.render_footer(frame, rects[1]);
+            }
+            CurrentScreen::DailyView => {
+                let all_day_count =
+                    get_all_day_events_by_day(&self.events, &self.selected_date, &self.filter)
+                        .len();
+                let all_day_height = match all_day_count {
+                    0 => 0,
+                    n => n as u16 + 2,
+                };
+                let vertical = &Layout::vertical([
+                    Constraint::Length(4),
+                    Constraint::Length(all_day_height),
+                    Constraint::Min(5),
+                    Constraint::Length(4),
+                    Constraint::Length(4),
+                ]);
+                let rects = vertical.split(frame.area());
+                self.render_date_bar(frame, rects[0]);
+                self.render_all_day_bar(frame, rects[1]);
+                self.render_table(frame, rects[2]);
+                self.render_scrollbar(frame, rects[2]);
+                self.render_info_bar(frame, rects[3]);
+                self.render_footer(frame, rects[4]);
+            }
+            CurrentScreen::TimeTableView => {
+                let vertical =
+                    &Layout::vertical([Constraint::Percentage(80), Constraint::Percentage(20)]);
+                let rects = vertical.split(frame.area());
+                self.render_timetable(frame, rects[0]);
+                self.render_info_col(frame, rects[1]);
+            }
+            CurrentScreen::FileSelect => {
+                let widget = self.file_explorer.widget();
+                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
+                let rects = vertical.split(frame.area());
+                frame.render_widget(&widget, rects[0]);
+                self.render_footer(frame, rects[1]);
+            }
+            CurrentScreen::ExamList => {
+                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
+                let rects = vertical.split(frame.area());
+                self.render_exam_list(frame, rects[0]);
+                self.render_footer(frame, rects[1]);
+            }
+            CurrentScreen::TeacherList => {
+                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
+                let rects = vertical.split(frame.area());
+                self.render_teacher_list(frame, rects[0]);
+                self.render_footer(frame, rects[1]);
+            }
+            CurrentScreen::SourceList => {
+                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
+                let rects = vertical.split(frame.area());
+                self.render_source_list(frame, rects[0]);
+                self.render_footer(frame, rects[1]);
+            }
+            CurrentScreen::Detail => {
+                let vertical = &Layout::vertical([Constraint::Min(5), Constraint::Length(4)]);
+                let rects = vertical.split(frame.area());
+                self.render_detail(frame, rects[0]);
+                self.render_footer(frame, rects[1]);
+            }
+            CurrentScreen::Diagnostics => {
+                let vertical = &Layout::vertical([
+                    Constraint::Length(5),
+                    Constraint::Min(5),
+                    Constraint::Length(4),
+                ]);
+                let rects = vertical.split(frame.area());
+                self.render_load_status(frame, rects[0]);
+                self.render_skipped_events(frame, rects[1]);
+                self.render_footer(frame, rects[2]);
+            }
+        }
+    }
+
+    fn render_file_not_found(&self, frame: &mut Frame, area: Rect) {
+        let sub_area = centered_rect(50, 15, area);
+        let error_box = Paragraph::new(Text::from_iter([
+            match &self.load_error {
+                Some(err) => err.to_string(),
+                None => "A megadott fájl nem található, vagy nem megfelelő formátumú.".to_owned(),
+            },
+            "Szeretnél megadni egy új elérési utat?".to_owned(),
+        ]))
+        .style(Style::new().fg(Color::Red))
+        .centered()
+        .block(
+            Block::bordered()
+                .title("HIBA")
+                .border_type(BorderType::Double)
+                .border_style(Style::new().fg(Color::Red))
+                .style(Style::new().bg(self.colors.buffer_bg)),
+        );
+        frame.render_widget(error_box, sub_area);
+    }
+
+    /// How many events each summary template matched, in the order they are tried.
+    fn template_usage(&self) -> String {
+        let used = self
+            .events
+            .iter()
+            .filter_map(|event| match (event.class(), event.exam()) {
+                (Some(class), _) => Some(class.template()),
+                (_, Some(exam)) => Some(exam.template()),
+                _ => None,
+            })
+            .collect::<Vec<&str>>();
+        let usage = self
+            .rules
+            .templates
+            .iter()
+            .map(|template| {
+                (
+                    template.name(),
+                    used.iter().filter(|&&name| name == template.name()).count(),
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| format!("{}: {}", name, count))
+            .collect::<Vec<String>>();
+        if usage.is_empty() {
+            "Egyik összefoglaló-sablon sem illeszkedett".to_owned()
+        } else {
+            format!("Összefoglaló-sablonok: {}", usage.join(", "))
+        }
+    }
+
+    fn render_load_status(&self, frame: &mut Frame, area: Rect) {
+        let (status, detail, color) = match &self.load_error {
+            Some(err) => {
+                let detail = match err {
+                    CalendarError::Syntax { message, .. } => message
+                        .lines()
+                        .find(|line| !line.trim().is_empty())
+                        .unwrap_or_default()
+                        .to_owned(),
+                    _ => String::new(),
+                };
+                (err.to_string(), detail, Color::Red)
+            }
+            None => (
+                format!(
+                    "{} esemény betöltve ({} nyelvű export)",
+                    self.events.len(),
+                    self.language.label()
+                ),
+                format!(
+                    "{} esemény csak nyers formában érhető el",
+                    self.skipped_events.len()
+                ),
+                self.colors.row_fg,
+            ),
+        };
+        let config = match &self.config_error {
+            Some(err) => err.to_string(),
+            None => self.template_usage(),
+        };
+        let status_bar = Paragraph::new(Text::from_iter([status, detail, config]))
+            .style(Style::new().fg(color).bg(self.colors.buffer_bg))
+            .centered()
+            .block(
+                Block::bordered()
+                    .title("Diagnosztika")
+                    .border_type(BorderType::Double)
+                    .border_style(Style::new().fg(color)),
+            );
+        frame.render_widget(status_bar, area);
+    }
+
+    fn render_skipped_events(&mut self, frame: &mut Frame, area: Rect) {
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+        let header = ["UID", "Összefoglaló", "Hiba"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(header_style)
+            .height(1);
+        let rows = self.skipped_events.iter().enumerate().map(|(i, skipped)| {
+            let color = match i % 2 {
+                0 => self.colors.normal_row_color,
+                _ => self.colors.alt_row_color,
+            };
+            [
+                skipped.uid.clone().unwrap_or_else(|| "-".to_owned()),
+                skipped.summary.clone().unwrap_or_else(|| "-".to_owned()),
+                skipped.reason.to_string(),
+            ]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::new().fg(self.colors.row_fg).bg(color))
+        });
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Percentage(25),
+                Constraint::Percentage(45),
+                Constraint::Percentage(30),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(selected_row_style)
+        .highlight_symbol("⮞")
+        .bg(self.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(t, area, &mut self.diagnostics_state);
+    }
+
+    fn render_detail(&self, frame: &mut Frame, area: Rect) {
+        let Some(event) = self.detail_event.and_then(|i| self.events.get(i)) else {
+            return;
+        };
+        let [name, code, time, location, people] = event.string_array(&self.timezone);
+        let date = event
+            .start()
+            .map(|start| {
+                start
+                    .with_timezone(&self.timezone)
+                    .format("%Y.%m.%d. ")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let mut lines = vec![
+            format!("Név: {}", name),
+            format!("Kód: {}", code),
+            format!("Típus: {}", event.kind().label()),
+            format!("Időpont: {}{}", date, time),
+            format!("Helyszín: {}", location),
+            format!("Oktatók: {}", people),
+        ];
+        if let Some(source) = event.source() {
+            lines.push(format!("Forrás: {}", source));
+        }
+        if let Some(location) = event
+            .location()
+            .filter(|location| location.pattern().is_some())
+        {
+            lines.push(format!(
+                "Épület: {}, emelet: {}, terem: {} ({})",
+                location.building().unwrap_or("-"),
+                location.floor().unwrap_or("-"),
+                location.room().unwrap_or("-"),
+                location.pattern().unwrap_or_default()
+            ));
+        }
+        if let Some(class) = event.class() {
+            if let Some(course) = self.course_of(event) {
+                let format_session = |session: Option<&NeptunClass>| {
+                    session.map_or("-".to_owned(), |session| {
+                        session
+                            .start_in(&self.timezone)
+                            .format("%Y.%m.%d. %H:%M")
+                            .to_string()
+                    })
+                };
+                let position = course
+                    .occurrences()
+                    .iter()
+                    .position(|session| session == class)
+                    .map_or(0, |i| i + 1);
+                lines.push(format!(
+                    "{} ({}) alkalmai: {}/{}",
+                    course.name(),
+                    course.code(),
+                    position,
+                    course.count()
+                ));
+                lines.push(format!("Első alkalom: {}", format_session(course.first())));
+                lines.push(format!("Utolsó alkalom: {}", format_session(course.last())));
+                lines.push(format!(
+                    "Következő alkalom: {}",
+                    format_session(course.next(&chrono::Utc::now()))
+                ));
+                if let Some(usual) = course.usual_location() {
+                    lines.push(format!("Szokásos terem: {}", usual));
+                }
+                if course.is_room_change(class) {
+                    lines.push("⚠ Teremváltozás: ez az alkalom máshol lesz".to_owned());
+                }
+            }
+        }
+        match event.properties() {
+            Some(properties) => {
+                lines.push(format!("UID: {}", properties.uid().unwrap_or("-")));
+                lines.push(format!(
+                    "Állapot: {}",
+                    match properties.status() {
+                        Some(EventStatus::Tentative) => "feltételes",
+                        Some(EventStatus::Confirmed) => "megerősített",
+                        Some(EventStatus::Cancelled) => "elmarad",
+                        None => "-",
+                    }
+                ));
+                lines.push(format!(
+                    "Utolsó módosítás: {}",
+                    properties
+                        .last_modified()
+                        .map_or("-".to_owned(), |modified| {
+                            modified
+                                .with_timezone(&self.timezone)
+                                .format("%Y.%m.%d. %H:%M")
+                                .to_string()
+                        })
+                ));
+                lines.push(format!(
+                    "Kategóriák: {}",
+                    properties.categories().join(", ")
+                ));
+                for (key, value) in properties.x_properties() {
+                    lines.push(format!("{}: {}", key, value));
+                }
+                if let Some(description) = properties.description() {
+                    lines.push(String::new());
+                    lines.extend(description.lines().map(|line| line.to_owned()));
+                }
+            }
+            None => lines.push(format!(
+                "UID: {}",
+                event.raw().uid.as_deref().unwrap_or("-")
+            )),
+        }
+        let detail = Paragraph::new(Text::from_iter(lines))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            )
+            .block(
+                Block::bordered()
+                    .title(event.title().to_owned())
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::new().fg(self.colors.footer_border_color)),
+            )
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, area);
+    }
+
+    fn render_source_list(&mut self, frame: &mut Frame, area: Rect) {
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+        let rows = self
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(i, (source, parsed))| {
+                let shown = if self.filter.is_source_hidden(source) {
+                    "□"
+                } else {
+                    "■"
+                };
+                // Events dropped as duplicates of another source still count here.
+                let kept = self
+                    .events
+                    .iter()
+                    .filter(|event| event.source() == Some(source.as_str()))
+                    .count();
+                [
+                    shown.to_owned(),
+                    source.clone(),
+                    format!("{}/{}", kept, parsed.events.len()),
+                ]
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(Style::new().fg(self.colors.row_fg).bg(match i % 2 {
+                    0 => self.colors.normal_row_color,
+                    _ => self.colors.alt_row_color,
+                }))
+            });
+        let sources = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Min(20),
+                Constraint::Length(12),
+            ],
+        )
+        .header(
+            ["", "Naptár", "Események"]
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(header_style)
+                .height(1),
+        )
+        .row_highlight_style(selected_row_style)
+        .highlight_symbol("⮞")
+        .bg(self.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always)
+        .block(
+            Block::bordered()
+                .title("Források")
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().fg(self.colors.footer_border_color)),
+        );
+        frame.render_stateful_widget(sources, area, &mut self.source_tablestate);
+    }
+
+    fn render_teacher_list(&mut self, frame: &mut Frame, area: Rect) {
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+        let row_style = |i: usize| {
+            Style::new().fg(self.colors.row_fg).bg(match i % 2 {
+                0 => self.colors.normal_row_color,
+                _ => self.colors.alt_row_color,
+            })
+        };
+        let directory = group_by_teacher(self.events.iter().filter_map(NeptunEvent::class));
+        let horizontal =
+            &Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]);
+        let rects = horizontal.split(area);
+
+        let teacher_rows = directory.iter().enumerate().map(|(i, (teacher, _))| {
+            let contact = self.contacts.get(teacher).cloned().unwrap_or_default();
+            [
+                teacher.name().to_owned(),
+                contact.email.unwrap_or_default(),
+                contact.office_hours.unwrap_or_default(),
+            ]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(row_style(i))
+        });
+        let teachers = Table::new(
+            teacher_rows,
+            [
+                Constraint::Min(self.longest_items_lens.4),
+                Constraint::Min(20),
+                Constraint::Min(20),
+            ],
+        )
+        .header(
+            ["Oktató", "E-mail", "Fogadóóra"]
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(header_style)
+                .height(1),
+        )
+        .row_highlight_style(selected_row_style)
+        .highlight_symbol("⮞")
+        .bg(self.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always)
+        .block(
+            Block::bordered()
+                .title("Oktatók")
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().fg(self.colors.footer_border_color)),
+        );
+        frame.render_stateful_widget(teachers, rects[0], &mut self.teacher_tablestate);
+
+        let selected = self
+            .teacher_tablestate
+            .selected()
+            .and_then(|i| directory.get(i.min(directory.len().saturating_sub(1))));
+        let courses = selected.map_or(Vec::new(), |(_, classes)| {
+            NeptunCourse::group(classes.iter().copied())
+        });
+        let now = chrono::Utc::now();
+        let course_rows = courses.iter().enumerate().map(|(i, course)| {
+            [
+                course.name().to_owned(),
+                course.code().to_owned(),
+                course
+                    .first()
+                    .map_or("", |class| class.class_type().label())
+                    .to_owned(),
+                course.count().to_string(),
+                course.next(&now).map_or("-".to_owned(), |class| {
+                    class
+                        .start_in(&self.timezone)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                }),
+            ]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(row_style(i))
+        });
+        let classes = Table::new(
+            course_rows,
+            [
+                Constraint::Min(self.longest_items_lens.0),
+                Constraint::Length(self.longest_items_lens.1 + 1),
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Length(17),
+            ],
+        )
+        .header(
+            ["Tárgy", "Kód", "Típus", "Alkalom", "Következő"]
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(header_style)
+                .height(1),
+        )
+        .bg(self.colors.buffer_bg)
+        .block(
+            Block::bordered()
+                .title(selected.map_or("Órák".to_owned(), |(teacher, _)| {
+                    format!("{} órái", teacher.name())
+                }))
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().fg(self.colors.footer_border_color)),
+        );
+        frame.render_widget(classes, rects[1]);
+    }
+
+    fn render_exam_list(&mut self, frame: &mut Frame, area: Rect) {
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+        let header = [
+            "Tárgy",
+            "Kód",
+            "Típus",
+            "Időpont",
+            "Terem",
+            "Vizsgáztatók",
+            "Hátravan",
+            "Szünet",
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(header_style)
+        .height(1);
+        let now = chrono::Utc::now();
+        let mut exams = self
+            .events
+            .iter()
+            .filter_map(NeptunEvent::exam)
+            .collect::<Vec<_>>();
+        exams.sort_by_key(|exam| exam.start);
+        let rows = exams.iter().enumerate().map(|(i, exam)| {
+            let color = match i % 2 {
+                0 => self.colors.normal_row_color,
+                _ => self.colors.alt_row_color,
+            };
+            let countdown = match exam.time_until(&now) {
+                Some(left) => format!("{} nap {} óra", left.num_days(), left.num_hours() % 24),
+                None => "lezajlott".to_owned(),
+            };
+            let gap = match i.checked_sub(1).map(|prev| exams[prev]) {
+                Some(prev) => format!("{} nap", exam.days_after(prev, &self.timezone)),
+                None => "-".to_owned(),
+            };
+            [
+                exam.course.to_owned(),
+                exam.code().to_owned(),
+                exam.exam_type().unwrap_or("-").to_owned(),
+                exam.start_in(&self.timezone)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                exam.room().to_string(),
+                join_names(exam.examiners(), ";"),
+                countdown,
+                gap,
+            ]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::new().fg(self.colors.row_fg).bg(color))
+        });
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Min(self.longest_items_lens.0 + 1),
+                Constraint::Length(self.longest_items_lens.1 + 1),
+                Constraint::Length(10),
+                Constraint::Length(17),
+                Constraint::Length(self.longest_items_lens.3 + 1),
+                Constraint::Min(self.longest_items_lens.4),
+                Constraint::Length(13),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(selected_row_style)
+        .highlight_symbol("⮞")
+        .bg(self.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always)
+        .block(
+            Block::bordered()
+                .title("Vizsgák")
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().fg(self.colors.footer_border_color)),
+        );
+        frame.render_stateful_widget(t, area, &mut self.exam_tablestate);
+    }
+
+    fn render_date_bar(&mut self, frame: &mut Frame, area: Rect) {
+        let info_footer = Paragraph::new(Text::from_iter([
+            self.selected_date.format("%Y-%m-%d").to_string(),
+            weekday_name(self.selected_date.weekday()).to_string(),
+        ]))
+        .style(
+            Style::new()
+                .fg(self.colors.row_fg)
+                .bg(self.colors.buffer_bg),
+        )
+        .centered()
+        .block(
+            Block::bordered()
+                .title(self.kind_filter_title())
+                .border_type(BorderType::Double)
+                .border_style(Style::new().fg(self.colors.footer_border_color)),
+        );
+        frame.render_widget(info_footer, area);
+    }
+
+    fn render_all_day_bar(&self, frame: &mut Frame, area: Rect) {
+        let events = get_all_day_events_by_day(&self.events, &self.selected_date, &self.filter);
+        if events.is_empty() {
+            return;
+        }
+        let all_day_bar = Paragraph::new(Text::from_iter(events.iter().map(|e| e.to_string())))
+            .style(Style::new().fg(Color::Yellow).bg(self.colors.buffer_bg))
+            .centered()
+            .block(
+                Block::bordered()
+                    .title("Egész napos")
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::new().fg(Color::Yellow)),
+            );
+        frame.render_widget(all_day_bar, area);
+    }
+
+    fn render_timetable(&mut self, frame: &mut Frame, area: Rect) {
+        let selected_classes = get_classes_by_week(
+            &self.events,
+            &self.selected_date,
+            &self.timezone,
+            &self.filter,
+        );
+        self.selected_classes = selected_classes.len();
+
+        let (monday, _) = week_bounds(&self.selected_date);
+        let tt = TimeTable::from_events(selected_classes, self.timezone).all_day_events(
+            get_all_day_events_by_week(&self.events, &self.selected_date, &self.filter),
+            monday,
+        );
+        self.timetablestate.set_distribution(&tt);
+        frame.render_stateful_widget(&tt, area, &mut self.timetablestate);
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+        let selected_classes = get_classes_by_day(
+            &self.events,
+            &self.selected_date,
+            &self.timezone,
+            &self.filter,
+        );
+        self.selected_classes = selected_classes.len();
+
+        let header = ["Név", "Kód", "Időpont", "Terem", "Tanárok"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(header_style)
+            .height(1);
+        let ongoing_idx = self.index_of_ongoing(&selected_classes);
+        let room_changes = selected_classes
+            .iter()
+            .map(|event| self.is_room_change(event))
+            .collect::<Vec<bool>>();
+        let rows = selected_classes.iter().enumerate().map(|(i, data)| {
+            let color = if Some(i) == ongoing_idx {
+                self.colors.ongoing_class
+            } else {
+                match i % 2 {
+                    0 => self.colors.normal_row_color,
+                    _ => self.colors.alt_row_color,
+                }
+            };
+            let style = if data.is_cancelled() {
+                Style::new()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            } else {
+                Style::new().fg(self.colors.row_fg)
+            };
+            let item = data.string_array(&self.timezone);
+            let room_change = room_changes[i];
+            item.into_iter()
+                .enumerate()
+                .map(|(i, content)| {
+                    let warning = match (i, room_change) {
+                        (3, true) => "⚠ teremváltozás",
+                        _ => "",
+                    };
+                    Cell::from(Text::from(format!(
+                        "\n{}\n{}",
+                        self.truncate_string(&content, i),
+                        warning
+                    )))
+                })
+                .collect::<Row>()
+                .style(style.bg(color))
+                .height(4)
+        });
+        // let bar = " █ ";
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Min(self.longest_items_lens.0 + 1),
+                Constraint::Length(self.longest_items_lens.1 + 1),
+                Constraint::Length(self.longest_items_lens.2 + 1),
+                Constraint::Length(self.longest_items_lens.3 + 1),
+                Constraint::Min(self.longest_items_lens.4),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(selected_row_style)
+        .highlight_symbol(Text::from(vec!["".into(), "⮞".into(), "".into()]))
+        .bg(self.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(t, area, &mut self.tablestate);
+    }
+
+    fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_stateful_widget(
+            Scrollbar::default()
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            &mut self.scroll_state,
+        );
+    }
+
+    fn render_info_bar(&self, frame: &mut Frame, area: Rect) {
+        let selected_classes = get_classes_by_day(
+            &self.events,
+            &self.selected_date,
+            &self.timezone,
+            &self.filter,
+        );
+        let info = match self.tablestate.selected() {
+            Some(i) => {
+                let str_arr = selected_classes[i].string_array(&self.timezone);
+                [str_arr[0].clone(), str_arr[4].clone()]
+            }
+            _ => ["".to_owned(), "".to_owned()],
+        };
+        let day = get_classes_by_day(
+            &self.events,
+            &self.selected_date,
+            &self.timezone,
+            &EventFilter::default(),
+        );
+        let breaks = free_time(day)
+            .iter()
+            .map(|(start, end)| {
+                format!(
+                    "{}-{}",
+                    start.with_timezone(&self.timezone).format("%H:%M"),
+                    end.with_timezone(&self.timezone).format("%H:%M")
+                )
+            })
+            .collect::<Vec<String>>();
+        let title = if breaks.is_empty() {
+            String::new()
+        } else {
+            format!("Szabad idő: {}", breaks.join(" | "))
+        };
+
+        let info_bar = Paragraph::new(Text::from_iter(info))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            )
+            .centered()
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::new().fg(self.colors.footer_border_color)),
+            );
+        frame.render_widget(info_bar, area);
+    }
+
+    fn render_info_col(&self, frame: &mut Frame, area: Rect) {
+        let selected_classes = get_classes_by_week(
+            &self.events,
+            &self.selected_date,
+            &self.timezone,
+            &self.filter,
+        );
+        let info = match self.timetablestate.index() {
+            Some(i) => selected_classes[i].string_array(&self.timezone),
+            _ => ["Név", "Kód", "Időtartam", "Tanárok", "Helyszín"].map(|x| x.to_owned()),
+        };
+        let hours = hours_by_type(
+            selected_classes
+                .iter()
+                .filter(|event| event.kind() == EventKind::Class)
+                .filter_map(|event| event.class()),
+        )
+        .iter()
+        .filter(|(_, duration)| !duration.is_zero())
+        .map(|(class_type, duration)| {
+            format!(
+                "{} {:.1}",
+                class_type.label(),
+                duration.num_minutes() as f64 / 60.0
+            )
+        })
+        .collect::<Vec<String>>();
+        let stats = if hours.is_empty() {
+            String::new()
+        } else {
+            format!("Heti óraszám: {}", hours.join(" | "))
+        };
+        let buildings = group_by_building(
+            selected_classes
+                .iter()
+                .filter(|event| event.kind() == EventKind::Class)
+                .filter_map(|event| event.class()),
+        )
+        .iter()
+        .map(|(building, classes)| format!("{} {}", building, classes.len()))
+        .collect::<Vec<String>>();
+        let buildings = if buildings.is_empty() {
+            String::new()
+        } else {
+            format!("Épületek: {}", buildings.join(" | "))
+        };
+
+        let info_bar = Paragraph::new(Text::from_iter(info.into_iter().chain([stats, buildings])))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            )
+            .centered()
+            .block(
+                Block::bordered()
+                    .title(self.kind_filter_title())
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::new().fg(self.colors.footer_border_color)),
+            )
+            .wrap(Wrap { trim: true });
+        frame.render_widget(info_bar, area);
+    }
+
+    fn render_footer(&self, frame: &mut Frame, area: Rect) {
+        let text = match self.current_screen {
+            CurrentScreen::DailyView => Text::from_iter(MAIN_INFO_TEXT),
+            CurrentScreen::TimeTableView => Text::from(""),
+            CurrentScreen::FileNotFound => Text::from(FILE_NOT_FOUND_INFO_TEXT),
+            CurrentScreen::FileSelect => Text::from_iter(FILE_SELECT_INFO_TEXT),
+            CurrentScreen::ExamList => Text::from_iter(EXAM_LIST_INFO_TEXT),
+            CurrentScreen::Diagnostics => Text::from_iter(DIAGNOSTICS_INFO_TEXT),
+            CurrentScreen::Detail => Text::from(DETAIL_INFO_TEXT),
+            CurrentScreen::TeacherList => Text::from_iter(TEACHER_LIST_INFO_TEXT),
+            CurrentScreen::SourceList => Text::from_iter(SOURCE_LIST_INFO_TEXT),
+        };
+        let info_footer = Paragraph::new(text)
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            )
+            .centered()
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Double)
+                    .border_style(Style::new().fg(match self.current_screen {
+                        CurrentScreen::DailyView => self.colors.footer_border_color,
+                        CurrentScreen::TimeTableView => Color::Magenta,
+                        CurrentScreen::FileSelect => Color::White,
+                        CurrentScreen::FileNotFound => Color::Red,
+                        CurrentScreen::ExamList => self.colors.footer_border_color,
+                        CurrentScreen::Diagnostics => Color::Yellow,
+                        CurrentScreen::Detail => self.colors.footer_border_color,
+                        CurrentScreen::TeacherList => self.colors.footer_border_color,
+                        CurrentScreen::SourceList => self.colors.footer_border_color,
+                    })),
+            );
+        frame.render_widget(info_footer, area);
+    }
+}
+
+struct TableColors {
+    buffer_bg: Color,
+    header_bg: Color,
+    header_fg: Color,
+    row_fg: Color,
+    selected_row_style_fg: Color,
+    normal_row_color: Color,
+    alt_row_color: Color,
+    footer_border_color: Color,
+    ongoing_class: Color,
+}
+
+impl TableColors {
+    fn new() -> Self {
+        Self {
+            buffer_bg: tailwind::SLATE.c950,
+            header_bg: tailwind::CYAN.c900,
+            header_fg: tailwind::SLATE.c200,
+            row_fg: tailwind::SLATE.c200,
+            selected_row_style_fg: tailwind::CYAN.c400,
+            // selected_column_style_fg: tailwind::CYAN.c400,
+            // selected_cell_style_fg: tailwind::CYAN.c600,
+            normal_row_color: tailwind::SLATE.c950,
+            alt_row_color: tailwind::SLATE.c900,
+            footer_border_color: tailwind::CYAN.c400,
+            ongoing_class: tailwind::SLATE.c600,
+        }
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
@@ -0,0 +1,496 @@
+use crate::eventproperties::EventProperties;
+use crate::exportlanguage::ExportLanguage;
+use crate::neptunclass::{OccurrenceKey, ParseError};
+use crate::neptunevent::NeptunEvent;
+use crate::parserules::ParseRules;
+use crate::recurrence::{expand, recurrence_id};
+use crate::summarytemplate::summary_kind;
+use chrono::{DateTime, Utc};
+use icalendar::{Calendar, CalendarComponent, Component, Event};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+use std::io;
+
+#[derive(Debug)]
+pub enum CalendarError {
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    Syntax {
+        path: String,
+        line: Option<usize>,
+        message: String,
+    },
+    NotACalendar {
+        path: String,
+    },
+}
+
+impl fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalendarError::Io { path, source } if source.kind() == io::ErrorKind::NotFound => {
+                write!(f, "A fájl nem található: {}", path)
+            }
+            CalendarError::Io { path, source } => {
+                write!(f, "A fájl nem olvasható ({}): {}", path, source)
+            }
+            CalendarError::Syntax {
+                path,
+                line: Some(line),
+                ..
+            } => write!(f, "iCalendar szintaktikai hiba: {}, {}. sor", path, line),
+            CalendarError::Syntax { path, .. } => {
+                write!(f, "iCalendar szintaktikai hiba: {}", path)
+            }
+            CalendarError::NotACalendar { path } => {
+                write!(f, "A fájl nem tartalmaz naptárat: {}", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CalendarError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// An event that could only be kept in its raw form, or a series whose recurrence rule could
+/// not be expanded, so only its first occurrence is shown.
+#[derive(Clone, Debug)]
+pub struct SkippedEvent {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub reason: ParseError,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ParsedCalendar {
+    pub language: ExportLanguage,
+    pub events: Vec<NeptunEvent>,
+    pub skipped: Vec<SkippedEvent>,
+}
+
+impl ParsedCalendar {
+    /// Marks every event as loaded from `source`.
+    pub fn with_source(mut self, source: &str) -> ParsedCalendar {
+        for event in &mut self.events {
+            event.set_source(source);
+        }
+        self
+    }
+}
+
+/// Merges calendars in the order they were loaded. An occurrence found in several of them is
+/// kept once: the copy modified last, or on a tie the one loaded later, so that a re-export
+/// replaces the events of an earlier one. The language is the first calendar's.
+pub fn merge(calendars: impl IntoIterator<Item = ParsedCalendar>) -> ParsedCalendar {
+    let mut merged = ParsedCalendar::default();
+    let mut seen: HashMap<OccurrenceKey, usize> = HashMap::new();
+    for (i, calendar) in calendars.into_iter().enumerate() {
+        if i == 0 {
+            merged.language = calendar.language;
+        }
+        merged.skipped.extend(calendar.skipped);
+        for event in calendar.events {
+            let Some(key) = event.occurrence_key() else {
+                merged.events.push(event);
+                continue;
+            };
+            match seen.get(&key) {
+                Some(&j) => {
+                    let modified = |event: &NeptunEvent| {
+                        event.properties().and_then(EventProperties::last_modified)
+                    };
+                    if modified(&event) >= modified(&merged.events[j]) {
+                        merged.events[j] = event;
+                    }
+                }
+                None => {
+                    seen.insert(key, merged.events.len());
+                    merged.events.push(event);
+                }
+            }
+        }
+    }
+    merged
+}
+
+pub fn parse_calendar(filename: &str) -> Result<Calendar, CalendarError> {
+    let file_contents = read_to_string(filename).map_err(|source| CalendarError::Io {
+        path: filename.to_owned(),
+        source,
+    })?;
+    let calendar: Calendar =
+        file_contents
+            .parse()
+            .map_err(|message: String| CalendarError::Syntax {
+                path: filename.to_owned(),
+                line: error_line(&message),
+                message,
+            })?;
+    if calendar.components.is_empty() && !file_contents.contains("BEGIN:VCALENDAR") {
+        return Err(CalendarError::NotACalendar {
+            path: filename.to_owned(),
+        });
+    }
+    Ok(calendar)
+}
+
+/// Picks the line number of the innermost error out of the icalendar parser's message.
+fn error_line(message: &str) -> Option<usize> {
+    let (_, after) = message.split_once("at line ")?;
+    after
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// The UID and RECURRENCE-ID of every event that replaces one occurrence of a series.
+fn overrides(cal: &Calendar) -> Vec<(&str, DateTime<Utc>)> {
+    cal.components
+        .iter()
+        .filter_map(|component| match component {
+            CalendarComponent::Event(event) => Some(event),
+            _ => None,
+        })
+        .filter(|event| event.property_value("RRULE").is_none())
+        .filter_map(|event| Some((event.get_uid()?, recurrence_id(event)?)))
+        .collect()
+}
+
+/// Parses every event of the calendar, trying the summary templates and location patterns in
+/// order. Recurring events are expanded into one event per occurrence.
+pub fn get_classes(cal: &Calendar, rules: &ParseRules) -> ParsedCalendar {
+    let mut parsed = ParsedCalendar {
+        language: ExportLanguage::detect(cal.components.iter().filter_map(|component| {
+            match component {
+                CalendarComponent::Event(event) => event
+                    .get_summary()
+                    .map(|summary| summary_kind(summary, &rules.templates)),
+                _ => None,
+            }
+        })),
+        ..Default::default()
+    };
+
+    let overrides = overrides(cal);
+    let skip = |event: &Event, reason| SkippedEvent {
+        uid: event.get_uid().map(|s| s.to_owned()),
+        summary: event.get_summary().map(|s| s.to_owned()),
+        reason,
+    };
+    for component in &cal.components {
+        if let CalendarComponent::Event(event) = component {
+            let overridden = overrides
+                .iter()
+                .filter(|(uid, _)| Some(*uid) == event.get_uid())
+                .map(|(_, recurrence_id)| *recurrence_id)
+                .collect::<Vec<DateTime<Utc>>>();
+            let occurrences = match expand(event, &overridden) {
+                Ok(occurrences) => occurrences,
+                Err(reason) => {
+                    parsed.skipped.push(skip(event, reason));
+                    vec![event.clone()]
+                }
+            };
+            for occurrence in &occurrences {
+                let (neptun_event, err) = NeptunEvent::parse(occurrence, parsed.language, rules);
+                if let Some(reason) = err {
+                    parsed.skipped.push(skip(occurrence, reason));
+                }
+                parsed.events.push(neptun_event);
+            }
+        }
+    }
+
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+    use crate::neptuncourse::NeptunCourse;
+    use crate::neptunevent::EventKind;
+    use chrono::NaiveDate;
+    use std::collections::HashSet;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:good\r
+SUMMARY:Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter;Nagy Anna - Tanóra\r
+DTSTART:20241120T080000Z\r
+DTEND:20241120T093000Z\r
+LOCATION:BA.1.13\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:no-code\r
+SUMMARY:Analízis I - Tanóra\r
+DTSTART:20241120T080000Z\r
+DTEND:20241120T093000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:no-end\r
+SUMMARY:Fizika ( - KMFIZ1EBNE) - Nagy Anna - Tanóra\r
+DTSTART:20241121T080000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:exam\r
+SUMMARY:Fizika ( - KMFIZ1EBNE) - Nagy Anna - Vizsga\r
+DTSTART:20250110T080000Z\r
+DTEND:20250110T100000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:consultation\r
+SUMMARY:Fizika ( - KMFIZ1EBNE) - Nagy Anna - Konzultáció\r
+DTSTART:20250108T080000Z\r
+DTEND:20250108T100000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday\r
+SUMMARY:Őszi szünet\r
+DTSTART;VALUE=DATE:20241028\r
+DTEND;VALUE=DATE:20241102\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:deadline\r
+SUMMARY:Tárgyjelentkezés vége\r
+DTSTART;VALUE=DATE:20240906\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn parse_calendar_tells_errors_apart() {
+        let dir = std::env::temp_dir();
+        let broken = dir.join("neptunics_broken.ics");
+        let garbage = dir.join("neptunics_garbage.ics");
+        std::fs::write(
+            &broken,
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nSUMMARY:x\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+        std::fs::write(&garbage, "this is not a calendar\n").unwrap();
+
+        assert!(matches!(
+            parse_calendar(dir.join("neptunics_missing.ics").to_str().unwrap()),
+            Err(CalendarError::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound
+        ));
+        assert!(matches!(
+            parse_calendar(broken.to_str().unwrap()),
+            Err(CalendarError::Syntax { line: Some(4), .. })
+        ));
+        assert!(matches!(
+            parse_calendar(garbage.to_str().unwrap()),
+            Err(CalendarError::NotACalendar { .. })
+        ));
+    }
+
+    #[test]
+    fn get_classes_keeps_every_event_and_reports_skipped() {
+        let cal: Calendar = CALENDAR.parse().unwrap();
+        let parsed = get_classes(&cal, &ParseRules::builtin());
+        assert_eq!(parsed.language, ExportLanguage::Hungarian);
+
+        let kinds = parsed
+            .events
+            .iter()
+            .map(|e| (e.raw().uid.as_deref().unwrap(), e.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("good", EventKind::Class),
+                ("no-code", EventKind::Class),
+                ("no-end", EventKind::Class),
+                ("exam", EventKind::Exam),
+                ("consultation", EventKind::Consultation),
+                ("holiday", EventKind::Other),
+                ("deadline", EventKind::Deadline),
+            ]
+        );
+        assert_eq!(parsed.events[0].class().unwrap().name, "Analízis I");
+        assert_eq!(parsed.events[0].class().unwrap().uid(), Some("good"));
+        assert_eq!(parsed.events[3].exam().unwrap().code(), "KMFIZ1EBNE");
+        assert!(parsed.events[4].class().is_some());
+
+        let all_day = parsed
+            .events
+            .iter()
+            .filter_map(|e| e.all_day())
+            .map(|e| (e.uid().unwrap(), e.first_day, e.last_day))
+            .collect::<Vec<_>>();
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert_eq!(
+            all_day,
+            vec![
+                ("holiday", date(10, 28), date(11, 1)),
+                ("deadline", date(9, 6), date(9, 6)),
+            ]
+        );
+
+        let reasons = parsed
+            .skipped
+            .iter()
+            .map(|s| (s.uid.as_deref().unwrap(), s.reason.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    "no-code",
+                    ParseError::BadSummary("Analízis I - Tanóra".to_owned())
+                ),
+                ("no-end", ParseError::MissingField("DTEND")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_hungarian_and_english_exports_alike() {
+        let summary = |language: &str| {
+            let cal: Calendar = match language {
+                "hu" => include_str!("../fixtures/neptun_hu.ics"),
+                _ => include_str!("../fixtures/neptun_en.ics"),
+            }
+            .parse()
+            .unwrap();
+            let parsed = get_classes(&cal, &ParseRules::builtin());
+            assert!(parsed.skipped.is_empty());
+            let events = parsed
+                .events
+                .iter()
+                .map(|e| {
+                    let (code, people) = match (e.class(), e.exam()) {
+                        (Some(class), _) => (
+                            class.code().to_owned(),
+                            class.teachers().iter().map(|t| t.to_string()).collect(),
+                        ),
+                        (_, Some(exam)) => (
+                            exam.code().to_owned(),
+                            exam.examiners().iter().map(|t| t.to_string()).collect(),
+                        ),
+                        _ => (String::new(), Vec::new()),
+                    };
+                    (
+                        e.kind(),
+                        code,
+                        people,
+                        e.span(),
+                        e.all_day().map(|a| a.first_day),
+                    )
+                })
+                .collect::<Vec<_>>();
+            (parsed.language, events)
+        };
+
+        let (hu_language, hu_events) = summary("hu");
+        let (en_language, en_events) = summary("en");
+        assert_eq!(hu_language, ExportLanguage::Hungarian);
+        assert_eq!(en_language, ExportLanguage::English);
+        assert_eq!(hu_events, en_events);
+        assert_eq!(
+            hu_events.iter().map(|e| e.0).collect::<Vec<_>>(),
+            vec![
+                EventKind::Class,
+                EventKind::Class,
+                EventKind::Exam,
+                EventKind::Consultation,
+                EventKind::Deadline,
+                EventKind::Other,
+            ]
+        );
+        assert_eq!(hu_events[1].2, ["Kiss Péter", "Nagy Anna"]);
+    }
+
+    #[test]
+    fn expands_recurring_events() {
+        let cal: Calendar = include_str!("../fixtures/recurring.ics").parse().unwrap();
+        let parsed = get_classes(&cal, &ParseRules::builtin());
+        let count = |uid: &str| {
+            parsed
+                .events
+                .iter()
+                .filter(|e| e.raw().uid.as_deref() == Some(uid))
+                .count()
+        };
+        assert_eq!(count("series-1"), 13);
+        assert_eq!(count("series-3"), 12);
+        assert_eq!(count("single"), 1);
+        // Four occurrences, one of them moved by a separate event.
+        assert_eq!(count("series-2"), 4);
+        assert_eq!(count("unsupported"), 1);
+        assert_eq!(
+            parsed
+                .skipped
+                .iter()
+                .map(|s| s.uid.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            ["unsupported"]
+        );
+
+        let analysis = parsed
+            .events
+            .iter()
+            .filter_map(|e| e.class())
+            .filter(|class| class.uid() == Some("series-1"))
+            .collect::<Vec<_>>();
+        assert_eq!(NeptunCourse::group(analysis.iter().copied())[0].count(), 13);
+        let times = analysis
+            .iter()
+            .map(|class| class.string_array(&DEFAULT_TIMEZONE)[2].clone())
+            .collect::<HashSet<_>>();
+        assert_eq!(times, HashSet::from(["08:15 - 09:45".to_owned()]));
+    }
+
+    #[test]
+    fn merges_calendars_and_keeps_sources() {
+        let parse = |text: &str, source: &str| {
+            get_classes(&text.parse().unwrap(), &ParseRules::builtin()).with_source(source)
+        };
+        let reexport = CALENDAR
+            .replace("Dr. Kiss Péter;Nagy Anna", "Nagy Anna")
+            .replace(
+                "UID:exam\r\n",
+                "UID:exam\r\nLAST-MODIFIED:20241101T000000Z\r\n",
+            );
+        let merged = merge([
+            parse(CALENDAR, "orarend.ics"),
+            parse(include_str!("../fixtures/neptun_hu.ics"), "masodik.ics"),
+            parse(&reexport, "ujra.ics"),
+        ]);
+        let count = |uid: &str| {
+            merged
+                .events
+                .iter()
+                .filter(|e| e.raw().uid.as_deref() == Some(uid))
+                .count()
+        };
+        assert_eq!(count("good"), 1);
+        assert_eq!(count("class-1"), 1);
+
+        let good = merged
+            .events
+            .iter()
+            .find(|e| e.raw().uid.as_deref() == Some("good"))
+            .unwrap();
+        assert_eq!(good.source(), Some("ujra.ics"));
+        assert_eq!(good.class().unwrap().teachers().len(), 1);
+        // The first export's exam has no LAST-MODIFIED, the re-export's is newer.
+        let exam = merged.events.iter().find(|e| e.exam().is_some()).unwrap();
+        assert_eq!(exam.source(), Some("ujra.ics"));
+        assert!(merged
+            .events
+            .iter()
+            .any(|e| e.source() == Some("masodik.ics")));
+    }
+}
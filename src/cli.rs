@@ -0,0 +1,437 @@
+use chrono::NaiveDate;
+use neptunics::calendar::CalendarError;
+use neptunics::coursecode::ClassType;
+use neptunics::eventfilter::EventFilter;
+#[cfg(feature = "export")]
+use neptunics::export::Format;
+#[cfg(feature = "export")]
+use neptunics::report::Report;
+use std::fmt;
+use std::io;
+
+pub const USAGE: &str = "\
+Használat: neptunics [PARANCS] [KAPCSOLÓK] [NAPTÁR.ics]...
+
+Megnyitja a megadott Neptun naptárexportokat, több fájl esetén összefésülve.
+Fájl nélkül a NeptunCalendarExport.ics-t keresi az aktuális könyvtárban.
+
+Parancsok, amelyek a felület helyett a kimenetre írnak:
+  today                a nap eseményei
+  tomorrow             a következő nap eseményei
+  next                 a következő esemény
+  week                 a hét eseményei
+  ics                  a tanórák és vizsgák iCalendar naptárként, más naptárakba
+                       importáláshoz
+  html                 a hét órarendje nyomtatható HTML oldalként (A4, fekvő)
+
+Kapcsolók:
+  --date ÉÉÉÉ-HH-NN    a megadott napon nyit, a parancsok ehhez a naphoz számolnak,
+                       a next a nap elejétől keres
+  --view daily|week    napi nézet vagy órarend (alapértelmezett: week)
+  --format text|json|csv|markdown
+                       a listázó parancsok kimenete (alapértelmezett: text)
+  --group CSOPORT      csak ezeknek a kurzuscsoportoknak az órái, például E1,GY2;
+                       többször is megadható
+  --type lecture|seminar|lab
+                       csak előadások, gyakorlatok vagy laborok
+  --no-cancelled       az elmaradt órák nélkül
+  -o, --output FÁJL    a parancs kimenete ebbe a fájlba kerül
+  --config FÁJL        beállításfájl a $NEPTUNICS_CONFIG helyett
+  -h, --help           ez a súgó
+  -V, --version        verziószám
+
+Kilépési kódok:
+  0   rendben
+  2   hibás parancssor
+  65  a fájl nem iCalendar naptár
+  66  a fájl nem található
+  73  a kimeneti fájl nem írható
+  74  a fájl nem olvasható
+  78  hibás beállításfájl
+";
+
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_DATAERR: u8 = 65;
+pub const EXIT_NOINPUT: u8 = 66;
+#[cfg(feature = "export")]
+pub const EXIT_CANTCREAT: u8 = 73;
+pub const EXIT_IOERR: u8 = 74;
+pub const EXIT_CONFIG: u8 = 78;
+
+/// The screen the app opens on once a calendar is loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum View {
+    Daily,
+    #[default]
+    Week,
+}
+
+/// The command names, which a build without the export feature rejects.
+#[cfg(not(feature = "export"))]
+const COMMANDS: [&str; 6] = ["today", "tomorrow", "next", "week", "ics", "html"];
+
+/// What a command prints instead of starting the TUI. Without the export feature there are no
+/// commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    #[cfg(feature = "export")]
+    Report(Report),
+    #[cfg(feature = "export")]
+    Ics,
+    #[cfg(feature = "export")]
+    Html,
+}
+
+impl Action {
+    /// The action a command name stands for, None for a calendar path.
+    #[cfg(feature = "export")]
+    pub fn from_name(name: &str) -> Result<Option<Action>, CliError> {
+        Ok(match name {
+            "ics" => Some(Action::Ics),
+            "html" => Some(Action::Html),
+            _ => Report::from_name(name).map(Action::Report),
+        })
+    }
+
+    #[cfg(not(feature = "export"))]
+    pub fn from_name(name: &str) -> Result<Option<Action>, CliError> {
+        if COMMANDS.contains(&name) {
+            Err(CliError::NoCommands(name.to_owned()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub action: Option<Action>,
+    pub calendars: Vec<String>,
+    pub date: Option<NaiveDate>,
+    pub view: View,
+    #[cfg(feature = "export")]
+    pub format: Format,
+    pub groups: Vec<String>,
+    pub class_type: Option<ClassType>,
+    pub hide_cancelled: bool,
+    pub output: Option<String>,
+    pub config: Option<String>,
+}
+
+impl Args {
+    /// The filter set by --group, --type and --no-cancelled, which the TUI starts with.
+    pub fn filter(&self) -> EventFilter {
+        let mut filter = EventFilter::default();
+        filter.set_groups(self.groups.clone());
+        filter.set_class_type(self.class_type);
+        if self.hide_cancelled {
+            filter.toggle_cancelled();
+        }
+        filter
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(&'static str),
+    BadDate(String),
+    BadView(String),
+    #[cfg(feature = "export")]
+    BadFormat(String),
+    BadType(String),
+    #[cfg(feature = "export")]
+    FormatWithoutCommand,
+    OutputWithoutCommand,
+    #[cfg(not(feature = "export"))]
+    NoCommands(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "ismeretlen kapcsoló: {}", option),
+            CliError::MissingValue(option) => write!(f, "hiányzó érték: {}", option),
+            CliError::BadDate(date) => {
+                write!(f, "hibás dátum (ÉÉÉÉ-HH-NN formában kell): {}", date)
+            }
+            CliError::BadView(view) => {
+                write!(f, "ismeretlen nézet (daily vagy week lehet): {}", view)
+            }
+            #[cfg(feature = "export")]
+            CliError::BadFormat(format) => write!(
+                f,
+                "ismeretlen formátum (text, json, csv vagy markdown lehet): {}",
+                format
+            ),
+            CliError::BadType(class_type) => write!(
+                f,
+                "ismeretlen óratípus (lecture, seminar vagy lab lehet): {}",
+                class_type
+            ),
+            #[cfg(feature = "export")]
+            CliError::FormatWithoutCommand => {
+                write!(
+                    f,
+                    "a --format csak a today, tomorrow, next és week parancsokkal használható"
+                )
+            }
+            CliError::OutputWithoutCommand => {
+                write!(f, "a --output csak parancsokkal használható")
+            }
+            #[cfg(not(feature = "export"))]
+            CliError::NoCommands(command) => write!(
+                f,
+                "ez a változat parancsok nélkül készült, csak a felületet ismeri: {}",
+                command
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parses the arguments after the program name. A command may only come before the calendar
+/// paths. Options take their value as the next argument or after '=', everything after "--" is
+/// a calendar path.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            parsed.calendars.extend(args);
+            break;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            if parsed.action.is_none() && parsed.calendars.is_empty() {
+                if let Some(action) = Action::from_name(&arg)? {
+                    parsed.action = Some(action);
+                    continue;
+                }
+            }
+            parsed.calendars.push(arg);
+            continue;
+        }
+        let (option, inline) = match arg.split_once('=') {
+            Some((option, value)) => (option, Some(value.to_owned())),
+            None => (arg.as_str(), None),
+        };
+        let mut value = |name: &'static str| {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or(CliError::MissingValue(name))
+        };
+        match option {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--date" => {
+                let date = value("--date")?;
+                parsed.date = Some(
+                    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                        .map_err(|_| CliError::BadDate(date))?,
+                );
+            }
+            "--view" => {
+                parsed.view = match value("--view")?.as_str() {
+                    "daily" => View::Daily,
+                    "week" => View::Week,
+                    view => return Err(CliError::BadView(view.to_owned())),
+                };
+            }
+            #[cfg(feature = "export")]
+            "--format" => {
+                let format = value("--format")?;
+                parsed.format = Format::from_name(&format).ok_or(CliError::BadFormat(format))?;
+            }
+            "--group" => parsed.groups.extend(
+                value("--group")?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|group| !group.is_empty())
+                    .map(str::to_owned),
+            ),
+            "--type" => {
+                parsed.class_type = Some(match value("--type")?.as_str() {
+                    "lecture" => ClassType::Lecture,
+                    "seminar" => ClassType::Seminar,
+                    "lab" => ClassType::Lab,
+                    class_type => return Err(CliError::BadType(class_type.to_owned())),
+                });
+            }
+            "--no-cancelled" => parsed.hide_cancelled = true,
+            "-o" | "--output" => parsed.output = Some(value("--output")?),
+            "--config" => parsed.config = Some(value("--config")?),
+            _ => return Err(CliError::UnknownOption(arg)),
+        }
+    }
+    #[cfg(feature = "export")]
+    if parsed.format != Format::Text && !matches!(parsed.action, Some(Action::Report(_))) {
+        return Err(CliError::FormatWithoutCommand);
+    }
+    if parsed.output.is_some() && parsed.action.is_none() {
+        return Err(CliError::OutputWithoutCommand);
+    }
+    Ok(Command::Run(parsed))
+}
+
+/// The exit code for a calendar given on the command line that could not be loaded.
+pub fn exit_code(err: &CalendarError) -> u8 {
+    match err {
+        CalendarError::Io { source, .. } if source.kind() == io::ErrorKind::NotFound => {
+            EXIT_NOINPUT
+        }
+        CalendarError::Io { .. } => EXIT_IOERR,
+        CalendarError::Syntax { .. } | CalendarError::NotACalendar { .. } => EXIT_DATAERR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neptunics::calendar::parse_calendar;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_arguments() {
+        assert_eq!(parse(&[]), Ok(Command::Run(Args::default())));
+        assert_eq!(
+            parse(&[
+                "a.ics",
+                "--date",
+                "2024-11-20",
+                "--view=daily",
+                "b.ics",
+                "--config",
+                "sajat.conf",
+                "--",
+                "--c.ics",
+            ]),
+            Ok(Command::Run(Args {
+                calendars: vec!["a.ics".into(), "b.ics".into(), "--c.ics".into()],
+                date: NaiveDate::from_ymd_opt(2024, 11, 20),
+                view: View::Daily,
+                config: Some("sajat.conf".into()),
+                ..Args::default()
+            }))
+        );
+        let filtered = parse(&[
+            "--group",
+            "E1, GY2",
+            "--group=L1",
+            "--type",
+            "seminar",
+            "--no-cancelled",
+        ]);
+        let Ok(Command::Run(args)) = filtered else {
+            panic!("{:?}", filtered);
+        };
+        assert_eq!(args.groups, ["E1", "GY2", "L1"]);
+        assert_eq!(args.class_type, Some(ClassType::Seminar));
+        assert!(args.hide_cancelled && args.filter().hides_cancelled());
+        assert_eq!(parse(&["a.ics", "--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["-V"]), Ok(Command::Version));
+
+        assert_eq!(
+            parse(&["--date", "2024.11.20."]),
+            Err(CliError::BadDate("2024.11.20.".into()))
+        );
+        assert_eq!(
+            parse(&["--view", "month"]),
+            Err(CliError::BadView("month".into()))
+        );
+        assert_eq!(
+            parse(&["-o", "ki.txt"]),
+            Err(CliError::OutputWithoutCommand)
+        );
+        assert_eq!(
+            parse(&["--type", "exam"]),
+            Err(CliError::BadType("exam".into()))
+        );
+        assert_eq!(
+            parse(&["--config"]),
+            Err(CliError::MissingValue("--config"))
+        );
+        assert_eq!(
+            parse(&["--colour"]),
+            Err(CliError::UnknownOption("--colour".into()))
+        );
+    }
+
+    #[cfg(feature = "export")]
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse(&["week", "--date=2024-11-20", "--format", "csv", "today"]),
+            Ok(Command::Run(Args {
+                action: Some(Action::Report(Report::Week)),
+                format: Format::Csv,
+                calendars: vec!["today".into()],
+                date: NaiveDate::from_ymd_opt(2024, 11, 20),
+                ..Args::default()
+            }))
+        );
+        let ics = parse(&["ics", "--group", "E1", "-o", "orarend.ics"]);
+        let Ok(Command::Run(args)) = ics else {
+            panic!("{:?}", ics);
+        };
+        assert_eq!(args.action, Some(Action::Ics));
+        assert_eq!(args.groups, ["E1"]);
+        assert_eq!(args.output.as_deref(), Some("orarend.ics"));
+        assert_eq!(
+            parse(&["html", "--date=2024-11-20"]),
+            Ok(Command::Run(Args {
+                action: Some(Action::Html),
+                date: NaiveDate::from_ymd_opt(2024, 11, 20),
+                ..Args::default()
+            }))
+        );
+
+        assert_eq!(
+            parse(&["today", "--format=xml"]),
+            Err(CliError::BadFormat("xml".into()))
+        );
+        assert_eq!(
+            parse(&["--format", "json"]),
+            Err(CliError::FormatWithoutCommand)
+        );
+        assert_eq!(
+            parse(&["ics", "--format", "json"]),
+            Err(CliError::FormatWithoutCommand)
+        );
+    }
+
+    #[cfg(not(feature = "export"))]
+    #[test]
+    fn rejects_commands_without_export() {
+        assert_eq!(
+            parse(&["today", "a.ics"]),
+            Err(CliError::NoCommands("today".into()))
+        );
+        assert_eq!(
+            parse(&["a.ics", "today"]),
+            Ok(Command::Run(Args {
+                calendars: vec!["a.ics".into(), "today".into()],
+                ..Args::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn maps_load_errors_to_exit_codes() {
+        let missing = parse_calendar("fixtures/nincs_ilyen.ics").unwrap_err();
+        assert_eq!(exit_code(&missing), EXIT_NOINPUT);
+        let not_a_calendar = parse_calendar("Cargo.toml").unwrap_err();
+        assert_eq!(exit_code(&not_a_calendar), EXIT_DATAERR);
+    }
+}
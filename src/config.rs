@@ -0,0 +1,194 @@
+use crate::location::LocationPattern;
+use crate::parserules::ParseRules;
+use crate::summarytemplate::SummaryTemplate;
+use chrono_tz::Tz;
+use std::fmt;
+use std::fs::read_to_string;
+use std::io;
+use std::path::PathBuf;
+
+pub const CONFIG_ENV_VAR: &str = "NEPTUNICS_CONFIG";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    Syntax {
+        path: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "A beállításfájl nem olvasható ({}): {}", path, source)
+            }
+            ConfigError::Syntax {
+                path,
+                line,
+                message,
+            } => write!(f, "Hibás beállítás: {}, {}. sor: {}", path, line, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Settings read from a `key = value` file, lines starting with '#' are comments:
+///
+/// ```text
+/// timezone = Europe/Budapest
+/// contacts = /home/me/oktatok.txt
+/// template obuda = [{code}] {name} / {type} / {teachers}
+/// location obuda = {building} épület {room}
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub timezone: Option<Tz>,
+    pub templates: Vec<SummaryTemplate>,
+    pub locations: Vec<LocationPattern>,
+    pub contacts: Option<PathBuf>,
+    /// The file the config was loaded from.
+    pub path: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn parse(text: &str, path: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (i, line) in text.lines().enumerate() {
+            let syntax = |message: String| ConfigError::Syntax {
+                path: path.to_owned(),
+                line: i + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax("hiányzó \"=\"".to_owned()))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key.split_once(char::is_whitespace) {
+                None if key == "timezone" => {
+                    config.timezone = Some(
+                        value
+                            .parse()
+                            .map_err(|_| syntax(format!("ismeretlen időzóna: {}", value)))?,
+                    );
+                }
+                None if key == "contacts" => config.contacts = Some(PathBuf::from(value)),
+                Some(("template", name)) => {
+                    let template = SummaryTemplate::new(name.trim(), value)
+                        .map_err(|err| syntax(err.to_string()))?;
+                    config.templates.push(template);
+                }
+                Some(("location", name)) => {
+                    let pattern = LocationPattern::new(name.trim(), value)
+                        .map_err(|err| syntax(err.to_string()))?;
+                    config.locations.push(pattern);
+                }
+                _ => return Err(syntax(format!("ismeretlen kulcs: {}", key))),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        let text = read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        let mut config = Config::parse(&text, path)?;
+        config.path = Some(PathBuf::from(path));
+        Ok(config)
+    }
+
+    /// `$NEPTUNICS_CONFIG`, or `neptunics/config` in the XDG config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(CONFIG_ENV_VAR) {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok()?;
+        Some(config_home.join("neptunics").join("config"))
+    }
+
+    /// Loads the default config file. A missing file is not an error, the defaults are used.
+    pub fn load_default() -> Result<Config, ConfigError> {
+        match Config::default_path() {
+            Some(path) if path.exists() => Config::load(&path.to_string_lossy()),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// The teacher contact file, `contacts` next to the config file unless set.
+    pub fn contacts_path(&self) -> Option<PathBuf> {
+        self.contacts.clone().or_else(|| {
+            self.path
+                .clone()
+                .or_else(Config::default_path)?
+                .parent()
+                .map(|dir| dir.join("contacts"))
+        })
+    }
+
+    /// The user's templates and location patterns first, then the built-in ones.
+    pub fn parse_rules(&self) -> ParseRules {
+        let builtin = ParseRules::builtin();
+        ParseRules {
+            templates: [self.templates.clone(), builtin.templates].concat(),
+            locations: [self.locations.clone(), builtin.locations].concat(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config_and_reports_bad_lines() {
+        let config = Config::parse(
+            "# saját egyetem\n\
+             timezone = Europe/London\n\
+             template obuda = [{code}] {name} / {type} / {teachers}\n\
+             location obuda = {building} épület {room}\n",
+            "config",
+        )
+        .unwrap();
+        assert_eq!(config.timezone, Some(chrono_tz::Europe::London));
+        let rules = config.parse_rules();
+        assert_eq!(rules.templates[0].name(), "obuda");
+        assert_eq!(rules.templates[1].name(), "neptun-group");
+        assert_eq!(rules.locations[0].name(), "obuda");
+        assert_eq!(rules.locations[1].name(), "building-floor-room");
+
+        assert!(matches!(
+            Config::parse("timezone = Europe/London\ncolour = red\n", "config"),
+            Err(ConfigError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            Config::parse("template x = {name}\n", "config"),
+            Err(ConfigError::Syntax { line: 1, message, .. }) if message.contains("{code}")
+        ));
+        assert!(matches!(
+            Config::parse("location x = {room}\n", "config"),
+            Err(ConfigError::Syntax { line: 1, message, .. }) if message.contains("{building}")
+        ));
+    }
+}
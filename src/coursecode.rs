@@ -0,0 +1,200 @@
+use crate::neptunclass::NeptunClass;
+use chrono::TimeDelta;
+use std::fmt;
+
+/// What a course group is, going by the letters of its identifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ClassType {
+    Lecture,
+    Seminar,
+    Lab,
+    #[default]
+    Unknown,
+}
+
+impl ClassType {
+    pub const ALL: [ClassType; 4] = [
+        ClassType::Lecture,
+        ClassType::Seminar,
+        ClassType::Lab,
+        ClassType::Unknown,
+    ];
+
+    /// E1, EA2 are lectures, GY2, SZ1 seminars and L01, LAB3 labs.
+    pub fn from_group(group: &str) -> ClassType {
+        let letters = group
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .to_uppercase();
+        match letters.as_str() {
+            "E" | "EA" => ClassType::Lecture,
+            "GY" | "SZ" => ClassType::Seminar,
+            "L" | "LAB" => ClassType::Lab,
+            _ => ClassType::Unknown,
+        }
+    }
+
+    /// The snake_case name in the JSON and CSV exports, the same as with serde.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClassType::Lecture => "lecture",
+            ClassType::Seminar => "seminar",
+            ClassType::Lab => "lab",
+            ClassType::Unknown => "unknown",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClassType::Lecture => "előadás",
+            ClassType::Seminar => "gyakorlat",
+            ClassType::Lab => "labor",
+            ClassType::Unknown => "egyéb",
+        }
+    }
+}
+
+/// A Neptun course code split into the subject code and the course group, such as
+/// "KMANA1EBNE" and "E1".
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CourseCode {
+    subject: String,
+    group: Option<String>,
+    class_type: ClassType,
+}
+
+impl CourseCode {
+    /// Takes the group from the summary when the template captured one, otherwise from the
+    /// end of the code: "KMANA1EBNE/E1" or "KMANA1EBNE_GY2".
+    pub fn parse(code: &str, group: Option<&str>) -> CourseCode {
+        let (subject, group) = match group.map(str::trim).filter(|g| !g.is_empty()) {
+            Some(group) => (code, Some(group)),
+            None => match code.rsplit_once(['/', '_']) {
+                Some((subject, group)) if is_group(group) => (subject, Some(group)),
+                _ => (code, None),
+            },
+        };
+        CourseCode {
+            subject: subject.to_owned(),
+            group: group.map(|g| g.to_owned()),
+            class_type: group.map_or(ClassType::Unknown, ClassType::from_group),
+        }
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    pub fn class_type(&self) -> ClassType {
+        self.class_type
+    }
+}
+
+impl fmt::Display for CourseCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.group {
+            Some(group) => write!(f, "{}/{}", self.subject, group),
+            None => write!(f, "{}", self.subject),
+        }
+    }
+}
+
+/// Letters followed by digits, like E1 or GY02.
+fn is_group(s: &str) -> bool {
+    let digits = s.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    digits.len() < s.len() && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Total length of the classes of each type, in the order of [`ClassType::ALL`]. Cancelled
+/// classes are not counted.
+pub fn hours_by_type<'a>(
+    classes: impl IntoIterator<Item = &'a NeptunClass>,
+) -> [(ClassType, TimeDelta); 4] {
+    let mut hours = ClassType::ALL.map(|class_type| (class_type, TimeDelta::zero()));
+    for class in classes.into_iter().filter(|class| !class.is_cancelled()) {
+        let i = ClassType::ALL
+            .iter()
+            .position(|class_type| *class_type == class.class_type())
+            .unwrap_or_default();
+        hours[i].1 += class.end - class.start;
+    }
+    hours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neptunclass::fixtures::class;
+    use crate::parserules::ParseRules;
+    use chrono::{DateTime, Utc};
+    use icalendar::{Component, Event, EventLike, EventStatus};
+
+    #[test]
+    fn parses_course_codes() {
+        let lecture = CourseCode::parse("KMANA1EBNE", Some("E1"));
+        assert_eq!(lecture.subject(), "KMANA1EBNE");
+        assert_eq!(lecture.group(), Some("E1"));
+        assert_eq!(lecture.class_type(), ClassType::Lecture);
+
+        let seminar = CourseCode::parse("KMANA1EBNE/GY2", None);
+        assert_eq!(seminar.subject(), "KMANA1EBNE");
+        assert_eq!(seminar.class_type(), ClassType::Seminar);
+        assert_eq!(seminar.to_string(), "KMANA1EBNE/GY2");
+
+        assert_eq!(
+            CourseCode::parse("KMFIZ1EBNE_L01", None).class_type(),
+            ClassType::Lab
+        );
+        let bare = CourseCode::parse("KMANA1EBNE", None);
+        assert_eq!(
+            (bare.group(), bare.class_type()),
+            (None, ClassType::Unknown)
+        );
+        assert_eq!(CourseCode::parse("MAT_ALAP", None).group(), None);
+    }
+
+    #[test]
+    fn sums_hours_by_type() {
+        let classes = [
+            class(
+                "Fizika I (L01 - KMFIZ1EBNE) - Nagy Anna - Tanóra",
+                "2024-11-18T08:00:00Z",
+                "2024-11-18T09:30:00Z",
+                "",
+            ),
+            class(
+                "Fizika I (L01 - KMFIZ1EBNE) - Nagy Anna - Tanóra",
+                "2024-11-20T08:00:00Z",
+                "2024-11-20T09:30:00Z",
+                "",
+            ),
+            class(
+                "Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra",
+                "2024-11-19T08:00:00Z",
+                "2024-11-19T10:00:00Z",
+                "",
+            ),
+            NeptunClass::from_event(
+                &Event::new()
+                    .summary("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra")
+                    .starts("2024-11-26T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                    .ends("2024-11-26T10:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                    .status(EventStatus::Cancelled)
+                    .done(),
+                &ParseRules::builtin(),
+            )
+            .unwrap(),
+        ];
+        assert!(classes[3].is_cancelled());
+        let hours = hours_by_type(&classes);
+        assert_eq!(hours[0], (ClassType::Lecture, TimeDelta::hours(2)));
+        assert_eq!(hours[1], (ClassType::Seminar, TimeDelta::zero()));
+        assert_eq!(hours[2], (ClassType::Lab, TimeDelta::hours(3)));
+    }
+}
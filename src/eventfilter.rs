@@ -0,0 +1,234 @@
+use crate::coursecode::ClassType;
+use crate::neptunevent::{EventKind, NeptunEvent};
+use std::collections::HashSet;
+
+/// Which events the daily table and the timetable show.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    hidden_kinds: HashSet<EventKind>,
+    class_type: Option<ClassType>,
+    building: Option<String>,
+    hide_cancelled: bool,
+    hidden_sources: HashSet<String>,
+    groups: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn shows(&self, event: &NeptunEvent) -> bool {
+        if self.hidden_kinds.contains(&event.kind())
+            || self.hide_cancelled && event.is_cancelled()
+            || event
+                .source()
+                .is_some_and(|source| self.hidden_sources.contains(source))
+        {
+            return false;
+        }
+        let class_type = match (self.class_type, event.kind()) {
+            (Some(class_type), EventKind::Class) => event
+                .class()
+                .is_some_and(|class| class.class_type() == class_type),
+            _ => true,
+        };
+        let group = match (self.groups.is_empty(), event.class()) {
+            (false, Some(class)) => class.course_code().group().is_none_or(|group| {
+                self.groups
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(group))
+            }),
+            _ => true,
+        };
+        let building = match (&self.building, event.location()) {
+            (Some(building), Some(location)) => location.building() == Some(building.as_str()),
+            _ => true,
+        };
+        class_type && group && building
+    }
+
+    pub fn is_hidden(&self, kind: EventKind) -> bool {
+        self.hidden_kinds.contains(&kind)
+    }
+
+    pub fn toggle_kind(&mut self, kind: EventKind) {
+        if !self.hidden_kinds.remove(&kind) {
+            self.hidden_kinds.insert(kind);
+        }
+    }
+
+    pub fn hides_cancelled(&self) -> bool {
+        self.hide_cancelled
+    }
+
+    pub fn toggle_cancelled(&mut self) {
+        self.hide_cancelled = !self.hide_cancelled;
+    }
+
+    pub fn is_source_hidden(&self, source: &str) -> bool {
+        self.hidden_sources.contains(source)
+    }
+
+    /// Hides or shows the events loaded from the calendar at `source`.
+    pub fn toggle_source(&mut self, source: &str) {
+        if !self.hidden_sources.remove(source) {
+            self.hidden_sources.insert(source.to_owned());
+        }
+    }
+
+    /// The only class type shown, `None` when every class is.
+    pub fn class_type(&self) -> Option<ClassType> {
+        self.class_type
+    }
+
+    pub fn set_class_type(&mut self, class_type: Option<ClassType>) {
+        self.class_type = class_type;
+    }
+
+    /// Only shows the classes of these course groups, such as "E1" and "GY2". A class whose
+    /// group is not known is still shown, and every class is when `groups` is empty.
+    pub fn set_groups(&mut self, groups: Vec<String>) {
+        self.groups = groups;
+    }
+
+    /// Steps through every class, lectures, seminars and labs.
+    pub fn cycle_class_type(&mut self) {
+        self.class_type = match self.class_type {
+            None => Some(ClassType::Lecture),
+            Some(ClassType::Lecture) => Some(ClassType::Seminar),
+            Some(ClassType::Seminar) => Some(ClassType::Lab),
+            Some(ClassType::Lab) | Some(ClassType::Unknown) => None,
+        };
+    }
+
+    /// The only building whose classes and exams are shown, `None` when every one is.
+    pub fn building(&self) -> Option<&str> {
+        self.building.as_deref()
+    }
+
+    /// Steps through every building, then each of `buildings` in order.
+    pub fn cycle_building(&mut self, buildings: &[String]) {
+        let next = match &self.building {
+            None => 0,
+            Some(building) => buildings
+                .iter()
+                .position(|b| b == building)
+                .map_or(buildings.len(), |i| i + 1),
+        };
+        self.building = buildings.get(next).cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exportlanguage::ExportLanguage;
+    use crate::parserules::ParseRules;
+    use chrono::{DateTime, Utc};
+    use icalendar::{Component, Event, EventLike, EventStatus};
+
+    fn event(summary: &str) -> NeptunEvent {
+        event_in(summary, "")
+    }
+
+    fn event_in(summary: &str, location: &str) -> NeptunEvent {
+        let (event, _) = NeptunEvent::parse(
+            &Event::new()
+                .summary(summary)
+                .location(location)
+                .starts("2024-11-20T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .ends("2024-11-20T09:30:00Z".parse::<DateTime<Utc>>().unwrap())
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        event
+    }
+
+    #[test]
+    fn filters_by_kind_and_class_type() {
+        let lecture = event("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let lab = event("Fizika I (L01 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let exam = event("Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Vizsga");
+        let mut filter = EventFilter::default();
+        assert!([&lecture, &lab, &exam].iter().all(|e| filter.shows(e)));
+
+        filter.cycle_class_type();
+        assert_eq!(filter.class_type(), Some(ClassType::Lecture));
+        assert!(filter.shows(&lecture) && !filter.shows(&lab) && filter.shows(&exam));
+
+        filter.toggle_kind(EventKind::Exam);
+        assert!(filter.is_hidden(EventKind::Exam) && !filter.shows(&exam));
+
+        for _ in 0..3 {
+            filter.cycle_class_type();
+        }
+        assert_eq!(filter.class_type(), None);
+        assert!(filter.shows(&lab));
+    }
+
+    #[test]
+    fn filters_by_building() {
+        let ba = event_in("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra", "BA.1.13");
+        let tg = event_in("Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Vizsga", "TG.4.21");
+        let break_day = event("Őszi szünet");
+        let buildings = ["BA".to_owned(), "TG".to_owned()];
+        let mut filter = EventFilter::default();
+
+        filter.cycle_building(&buildings);
+        assert_eq!(filter.building(), Some("BA"));
+        assert!(filter.shows(&ba) && !filter.shows(&tg) && filter.shows(&break_day));
+
+        filter.cycle_building(&buildings);
+        assert!(!filter.shows(&ba) && filter.shows(&tg));
+        filter.cycle_building(&buildings);
+        assert_eq!(filter.building(), None);
+    }
+
+    #[test]
+    fn hides_cancelled_events_on_request() {
+        let (cancelled, _) = NeptunEvent::parse(
+            &Event::new()
+                .summary("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra")
+                .starts("2024-11-20T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .ends("2024-11-20T09:30:00Z".parse::<DateTime<Utc>>().unwrap())
+                .status(EventStatus::Cancelled)
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        let held = event("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let mut filter = EventFilter::default();
+        assert!(filter.shows(&cancelled));
+
+        filter.toggle_cancelled();
+        assert!(filter.hides_cancelled());
+        assert!(!filter.shows(&cancelled) && filter.shows(&held));
+    }
+
+    #[test]
+    fn hides_sources() {
+        let mut first = event("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        first.set_source("orarend.ics");
+        let mut second = first.clone();
+        second.set_source("vizsgak.ics");
+        let unknown = event("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let mut filter = EventFilter::default();
+
+        filter.toggle_source("orarend.ics");
+        assert!(filter.is_source_hidden("orarend.ics"));
+        assert!(!filter.shows(&first) && filter.shows(&second) && filter.shows(&unknown));
+        filter.toggle_source("orarend.ics");
+        assert!(filter.shows(&first));
+    }
+
+    #[test]
+    fn filters_by_group() {
+        let e1 = event("Fizika I (E1 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let gy2 = event("Fizika I (GY2 - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let unknown = event("Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Tanóra");
+        let mut filter = EventFilter::default();
+        filter.set_groups(vec!["e1".to_owned()]);
+        assert!(filter.shows(&e1) && !filter.shows(&gy2) && filter.shows(&unknown));
+
+        filter.set_class_type(Some(ClassType::Seminar));
+        assert!(!filter.shows(&e1));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::recurrence;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use icalendar::{Component, Event, EventStatus};
+use std::collections::BTreeMap;
+
+/// The iCalendar properties of an event besides the ones the model is parsed from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventProperties {
+    uid: Option<String>,
+    recurrence_id: Option<DateTime<Utc>>,
+    description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::status"))]
+    status: Option<EventStatus>,
+    last_modified: Option<DateTime<Utc>>,
+    categories: Vec<String>,
+    x_properties: BTreeMap<String, String>,
+}
+
+impl EventProperties {
+    pub fn uid(&self) -> Option<&str> {
+        self.uid.as_deref()
+    }
+
+    /// The original start of the occurrence when the event is one of a recurring series.
+    pub fn recurrence_id(&self) -> Option<DateTime<Utc>> {
+        self.recurrence_id
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn status(&self) -> Option<EventStatus> {
+        self.status
+    }
+
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.last_modified
+    }
+
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// Every `X-` property by name, such as `X-NEPTUN-ID`.
+    pub fn x_properties(&self) -> &BTreeMap<String, String> {
+        &self.x_properties
+    }
+
+    pub fn x_property(&self, name: &str) -> Option<&str> {
+        self.x_properties.get(name).map(|s| s.as_str())
+    }
+}
+
+/// The lowercase name of an iCalendar STATUS in the JSON export and with serde.
+pub fn status_name(status: EventStatus) -> &'static str {
+    match status {
+        EventStatus::Tentative => "tentative",
+        EventStatus::Confirmed => "confirmed",
+        EventStatus::Cancelled => "cancelled",
+    }
+}
+
+pub fn status_from_name(name: &str) -> Option<EventStatus> {
+    [
+        EventStatus::Tentative,
+        EventStatus::Confirmed,
+        EventStatus::Cancelled,
+    ]
+    .into_iter()
+    .find(|status| status_name(*status) == name)
+}
+
+impl From<&Event> for EventProperties {
+    fn from(event: &Event) -> Self {
+        EventProperties {
+            uid: event.get_uid().map(|s| s.to_owned()),
+            recurrence_id: recurrence::recurrence_id(event),
+            description: event.get_description().map(|s| s.to_owned()),
+            status: event.get_status(),
+            // `Component::get_last_modified` looks the property up as "LAST_MODIFIED".
+            last_modified: event
+                .property_value("LAST-MODIFIED")
+                .and_then(|value| NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ").ok())
+                .map(|naive| naive.and_utc()),
+            // CATEGORIES may repeat, so the parser files it under the multi-properties.
+            categories: event
+                .multi_properties()
+                .get("CATEGORIES")
+                .into_iter()
+                .flatten()
+                .flat_map(|property| property.value().split(','))
+                .map(|s| s.trim().to_owned())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            x_properties: event
+                .properties()
+                .iter()
+                .filter(|(key, _)| key.starts_with("X-"))
+                .map(|(key, property)| (key.to_owned(), property.value().to_owned()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icalendar::{Calendar, CalendarComponent};
+
+    #[test]
+    fn keeps_neptun_properties() {
+        let cal: Calendar = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:neptun-123\r
+SUMMARY:Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra\r
+DESCRIPTION:Tárgy: Analízis I\\nKurzus: E1\\, elmélet\r
+STATUS:CANCELLED\r
+LAST-MODIFIED:20241115T120000Z\r
+CATEGORIES:Tanóra,Előadás\r
+X-NEPTUN-COURSE:E1\r
+DTSTART:20241120T080000Z\r
+DTEND:20241120T093000Z\r
+END:VEVENT\r
+END:VCALENDAR\r
+"
+        .parse()
+        .unwrap();
+        let Some(CalendarComponent::Event(event)) = cal.components.first() else {
+            panic!("no event");
+        };
+        let properties = EventProperties::from(event);
+
+        assert_eq!(properties.uid(), Some("neptun-123"));
+        assert_eq!(
+            properties.description(),
+            Some("Tárgy: Analízis I\nKurzus: E1, elmélet")
+        );
+        assert_eq!(properties.status(), Some(EventStatus::Cancelled));
+        assert_eq!(
+            properties.last_modified(),
+            Some("2024-11-15T12:00:00Z".parse().unwrap())
+        );
+        assert_eq!(properties.categories(), ["Tanóra", "Előadás"]);
+        assert_eq!(properties.x_property("X-NEPTUN-COURSE"), Some("E1"));
+        assert_eq!(properties.x_properties().len(), 1);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::coursecode::CourseCode;
+use crate::eventproperties::{status_name, EventProperties};
+use crate::location::Location;
+use crate::neptunevent::NeptunEvent;
+use crate::schedule::weekday_name;
+use crate::teacher::{join_names, Teacher};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// How the listing commands print the events they select.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "markdown" => Some(Format::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// The days of a listing, each with its all-day events first and then its timed events.
+pub type Days<'a> = [(NaiveDate, Vec<&'a NeptunEvent>)];
+
+/// Every event of the listing once, an all-day event spanning several days included.
+fn distinct<'a>(days: &Days<'a>) -> Vec<&'a NeptunEvent> {
+    let mut events: Vec<&NeptunEvent> = Vec::new();
+    for event in days.iter().flat_map(|(_, events)| events) {
+        if !events.iter().any(|seen| std::ptr::eq(*seen, *event)) {
+            events.push(event);
+        }
+    }
+    events
+}
+
+enum Json {
+    Null,
+    Bool(bool),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn str(value: &str) -> Json {
+        Json::Str(value.to_owned())
+    }
+
+    fn opt(value: Option<&str>) -> Json {
+        value.map_or(Json::Null, Json::str)
+    }
+
+    fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Str(value) => {
+                write!(f, "\"")?;
+                for c in value.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\t' => write!(f, "\\t")?,
+                        c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(f, "{}{}", separator, item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(f, "{}{}:{}", separator, Json::str(name), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// ISO-8601 with the offset of the display timezone.
+fn iso_time(time: DateTime<Utc>, tz: &Tz) -> String {
+    time.with_timezone(tz)
+        .format("%Y-%m-%dT%H:%M:%S%:z")
+        .to_string()
+}
+
+fn json_teachers(teachers: &[Teacher]) -> Json {
+    Json::Array(teachers.iter().map(|t| Json::str(t.name())).collect())
+}
+
+fn json_location(location: &Location) -> Json {
+    Json::object(vec![
+        ("raw", Json::str(location.raw())),
+        ("building", Json::opt(location.building())),
+        ("floor", Json::opt(location.floor())),
+        ("room", Json::opt(location.room())),
+        ("pattern", Json::opt(location.pattern())),
+    ])
+}
+
+fn json_properties(properties: &EventProperties, tz: &Tz) -> Json {
+    let time =
+        |time: Option<DateTime<Utc>>| time.map_or(Json::Null, |t| Json::Str(iso_time(t, tz)));
+    Json::object(vec![
+        ("description", Json::opt(properties.description())),
+        ("status", Json::opt(properties.status().map(status_name))),
+        ("last_modified", time(properties.last_modified())),
+        ("recurrence_id", time(properties.recurrence_id())),
+        (
+            "categories",
+            Json::Array(
+                properties
+                    .categories()
+                    .iter()
+                    .map(|c| Json::str(c))
+                    .collect(),
+            ),
+        ),
+        (
+            "x_properties",
+            Json::Object(
+                properties
+                    .x_properties()
+                    .iter()
+                    .map(|(name, value)| (name.clone(), Json::str(value)))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn json_event(event: &NeptunEvent, tz: &Tz) -> Json {
+    let (start, end) = match event.span() {
+        Some((start, end)) => (Json::Str(iso_time(start, tz)), Json::Str(iso_time(end, tz))),
+        None => (Json::Null, Json::Null),
+    };
+    let mut fields: Vec<(&str, Json)> = vec![
+        ("kind", Json::str(event.kind().name())),
+        ("title", Json::str(event.title())),
+        ("uid", Json::opt(event.raw().uid.as_deref())),
+        ("source", Json::opt(event.source())),
+        ("start", start),
+        ("end", end),
+        ("timezone", Json::str(tz.name())),
+        ("cancelled", Json::Bool(event.is_cancelled())),
+        (
+            "location",
+            event.location().map_or(Json::Null, json_location),
+        ),
+    ];
+    if let Some(class) = event.class() {
+        fields.push((
+            "class",
+            Json::object(vec![
+                ("name", Json::str(&class.name)),
+                ("code", Json::str(class.code())),
+                ("subject", Json::str(class.course_code().subject())),
+                ("group", Json::opt(class.course_code().group())),
+                ("class_type", Json::str(class.class_type().name())),
+                ("teachers", json_teachers(class.teachers())),
+                ("template", Json::str(class.template())),
+            ]),
+        ));
+    }
+    if let Some(exam) = event.exam() {
+        let course_code = CourseCode::parse(exam.code(), None);
+        fields.push((
+            "exam",
+            Json::object(vec![
+                ("course", Json::str(&exam.course)),
+                ("code", Json::str(exam.code())),
+                ("subject", Json::str(course_code.subject())),
+                ("group", Json::opt(course_code.group())),
+                ("exam_type", Json::opt(exam.exam_type())),
+                ("examiners", json_teachers(exam.examiners())),
+                ("template", Json::str(exam.template())),
+            ]),
+        ));
+    }
+    if let Some(all_day) = event.all_day() {
+        fields.push((
+            "all_day",
+            Json::object(vec![
+                ("summary", Json::str(&all_day.summary)),
+                ("first_day", Json::Str(all_day.first_day.to_string())),
+                ("last_day", Json::Str(all_day.last_day.to_string())),
+            ]),
+        ));
+    }
+    if let Some(properties) = event.properties() {
+        fields.push(("properties", json_properties(properties, tz)));
+    }
+    Json::object(fields)
+}
+
+/// A JSON array with every parsed field of the events, times in ISO-8601 with the offset of
+/// `tz`, whose name is given with each event.
+///
+/// This is a flat schema for scripts working on a listing, not the serde encoding of the
+/// model in [`crate::serialization`]: an event is one object with its kind, name and times at
+/// the top, the raw VEVENT is left out and times are local. The kind, class type and status
+/// names are the same in both.
+pub fn to_json(days: &Days, tz: &Tz) -> String {
+    let events = distinct(days)
+        .into_iter()
+        .map(|e| json_event(e, tz))
+        .collect();
+    format!("{}\n", Json::Array(events))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+const CSV_HEADER: [&str; 15] = [
+    "date",
+    "start",
+    "end",
+    "kind",
+    "name",
+    "code",
+    "group",
+    "class_type",
+    "teachers",
+    "location",
+    "building",
+    "floor",
+    "room",
+    "cancelled",
+    "uid",
+];
+
+/// RFC 4180 CSV with a header row, one event per row with the date and times in separate
+/// local columns so that spreadsheets can sort and sum them.
+pub fn to_csv(days: &Days, tz: &Tz) -> String {
+    let mut rows = vec![CSV_HEADER.map(str::to_owned).to_vec()];
+    for event in distinct(days) {
+        let (date, start, end) = match (event.span(), event.all_day()) {
+            (Some((start, end)), _) => {
+                let (start, end) = (start.with_timezone(tz), end.with_timezone(tz));
+                (
+                    start.date_naive().to_string(),
+                    start.format("%H:%M").to_string(),
+                    end.format("%H:%M").to_string(),
+                )
+            }
+            (None, Some(all_day)) => (all_day.first_day.to_string(), String::new(), String::new()),
+            (None, None) => Default::default(),
+        };
+        let location = event.location();
+        let part = |part: fn(&Location) -> Option<&str>| {
+            location.and_then(part).unwrap_or_default().to_owned()
+        };
+        let (code, group, class_type, teachers) = match (event.class(), event.exam()) {
+            (Some(class), _) => (
+                class.course_code().subject().to_owned(),
+                class.course_code().group().unwrap_or_default().to_owned(),
+                class.class_type().name().to_owned(),
+                join_names(class.teachers(), "; "),
+            ),
+            (_, Some(exam)) => {
+                let course_code = CourseCode::parse(exam.code(), None);
+                (
+                    course_code.subject().to_owned(),
+                    course_code.group().unwrap_or_default().to_owned(),
+                    String::new(),
+                    join_names(exam.examiners(), "; "),
+                )
+            }
+            _ => Default::default(),
+        };
+        rows.push(vec![
+            date,
+            start,
+            end,
+            event.kind().name().to_owned(),
+            event.title().to_owned(),
+            code,
+            group,
+            class_type,
+            teachers,
+            location.map_or_else(
+                || event.raw().location.clone().unwrap_or_default(),
+                |location| location.raw().to_owned(),
+            ),
+            part(Location::building),
+            part(Location::floor),
+            part(Location::room),
+            event.is_cancelled().to_string(),
+            event.raw().uid.clone().unwrap_or_default(),
+        ]);
+    }
+    rows.iter()
+        .map(|row| {
+            let fields = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+            format!("{}\r\n", fields.join(","))
+        })
+        .collect()
+}
+
+fn markdown_cell(event: &NeptunEvent, tz: &Tz) -> String {
+    let [name, code, _, location, _] = event.string_array(tz);
+    let name = match (event.is_cancelled(), code.is_empty()) {
+        (true, _) => format!("~~{}~~ (elmaradt)", name),
+        (false, true) => format!("**{}**", name),
+        (false, false) => format!("**{}** ({})", name, code),
+    };
+    let cell = if location.is_empty() {
+        name
+    } else {
+        format!("{}<br>{}", name, location)
+    };
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+/// A table with a column for each day and a row for each time slot, all-day events in the
+/// first row.
+pub fn to_markdown(days: &Days, tz: &Tz) -> String {
+    let slot = |event: &NeptunEvent| {
+        event.span().map(|(start, end)| {
+            format!(
+                "{}-{}",
+                start.with_timezone(tz).format("%H:%M"),
+                end.with_timezone(tz).format("%H:%M")
+            )
+        })
+    };
+    let mut slots = days
+        .iter()
+        .flat_map(|(_, events)| events.iter().filter_map(|event| slot(event)))
+        .collect::<Vec<String>>();
+    slots.sort();
+    slots.dedup();
+
+    let mut out = String::from("| Idő |");
+    for (day, _) in days {
+        out.push_str(&format!(
+            " {} {} |",
+            weekday_name(day.weekday()),
+            day.format("%m.%d.")
+        ));
+    }
+    out.push_str(&format!("\n|---|{}\n", "---|".repeat(days.len())));
+    let mut row = |label: &str, slot_of: &dyn Fn(&NeptunEvent) -> Option<String>| {
+        out.push_str(&format!("| {} |", label));
+        for (_, events) in days {
+            let cells = events
+                .iter()
+                .filter(|event| slot_of(event).as_deref() == Some(label))
+                .map(|event| markdown_cell(event, tz))
+                .collect::<Vec<String>>();
+            out.push_str(&format!(" {} |", cells.join("<br>")));
+        }
+        out.push('\n');
+    };
+    if days
+        .iter()
+        .any(|(_, events)| events.iter().any(|event| event.all_day().is_some()))
+    {
+        row("Egész nap", &|event: &NeptunEvent| {
+            event.all_day().map(|_| "Egész nap".to_owned())
+        });
+    }
+    for label in &slots {
+        row(label, &slot);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::get_classes;
+    use crate::exportlanguage::ExportLanguage;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+    use crate::parserules::ParseRules;
+    use icalendar::{Component, Event, EventLike};
+
+    const CALENDAR: &str = include_str!("../fixtures/neptun_hu.ics");
+
+    fn days(events: &[NeptunEvent]) -> Vec<(NaiveDate, Vec<&NeptunEvent>)> {
+        let tz = DEFAULT_TIMEZONE;
+        let mut days: Vec<(NaiveDate, Vec<&NeptunEvent>)> = Vec::new();
+        for event in events.iter().filter(|event| event.class().is_some()) {
+            let day = event.start().unwrap().with_timezone(&tz).date_naive();
+            match days.iter_mut().find(|(d, _)| *d == day) {
+                Some((_, events)) => events.push(event),
+                None => days.push((day, vec![event])),
+            }
+        }
+        days.sort_by_key(|(day, _)| *day);
+        days
+    }
+
+    #[test]
+    fn writes_json_csv_and_markdown() {
+        let events = get_classes(&CALENDAR.parse().unwrap(), &ParseRules::builtin()).events;
+        let days = days(&events);
+        let tz = DEFAULT_TIMEZONE;
+
+        let json = to_json(&days, &tz);
+        assert!(json.starts_with("[{\"kind\":\"class\""));
+        assert!(json.contains("\"start\":\"2024-11-18T08:15:00+01:00\""));
+        assert!(json.contains("\"timezone\":\"Europe/Budapest\""));
+        assert!(json.contains("\"building\":\"BA\""));
+        assert_eq!(
+            Json::str("a \"b\"\n\u{1}").to_string(),
+            "\"a \\\"b\\\"\\n\\u0001\""
+        );
+
+        let csv = to_csv(&days, &tz);
+        let lines = csv.split("\r\n").collect::<Vec<&str>>();
+        assert!(lines[0].starts_with("date,start,end,kind,name"));
+        assert!(lines[1].starts_with("2024-11-18,08:15,09:45,class,Analízis I,KMANA1EBNE"));
+        // The code column holds the subject code for exams as well, with the group apart.
+        let (exam, _) = NeptunEvent::parse(
+            &Event::new()
+                .summary("Fizika I ( - KMFIZ1EBNE/GY2) - Nagy Anna - Vizsga")
+                .starts("2025-01-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .ends("2025-01-10T10:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        let exam_day = [(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(), vec![&exam])];
+        let row = to_csv(&exam_day, &tz);
+        assert_eq!(
+            row.split("\r\n").nth(1),
+            Some("2025-01-10,09:00,11:00,exam,Fizika I,KMFIZ1EBNE,GY2,,Nagy Anna,,,,,false,")
+        );
+        assert!(to_json(&exam_day, &tz)
+            .contains("\"code\":\"KMFIZ1EBNE/GY2\",\"subject\":\"KMFIZ1EBNE\",\"group\":\"GY2\""));
+        assert_eq!(
+            csv_field("Kiss, Péter \"KP\""),
+            "\"Kiss, Péter \"\"KP\"\"\""
+        );
+
+        let markdown = to_markdown(&days, &tz);
+        let rows = markdown.lines().collect::<Vec<&str>>();
+        assert!(rows[0].starts_with("| Idő | Hétfő 11.18. | Kedd 11.19. |"));
+        assert!(rows[1].starts_with("|---|---|---|"));
+        assert!(rows[2].starts_with("| 08:15-09:45 | **Analízis I** (KMANA1EBNE)<br>BA.1.13 |"));
+    }
+}
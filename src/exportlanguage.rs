@@ -0,0 +1,85 @@
+use crate::neptunevent::EventKind;
+
+/// The language Neptun was set to when the calendar was exported. It decides the words that
+/// name the event types, the summary layout is handled the same way for both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ExportLanguage {
+    #[default]
+    Hungarian,
+    English,
+}
+
+impl ExportLanguage {
+    pub const ALL: [ExportLanguage; 2] = [ExportLanguage::Hungarian, ExportLanguage::English];
+
+    /// Picks the language whose event type words match the most summaries, Hungarian on a tie.
+    /// Takes the type part of each summary, see [`crate::summarytemplate::summary_kind`].
+    pub fn detect<'s>(kinds: impl IntoIterator<Item = &'s str>) -> ExportLanguage {
+        let mut matches = [0usize; 2];
+        for kind in kinds {
+            for (i, language) in ExportLanguage::ALL.iter().enumerate() {
+                if EventKind::detect(kind, *language) != EventKind::Other {
+                    matches[i] += 1;
+                }
+            }
+        }
+        if matches[1] > matches[0] {
+            ExportLanguage::English
+        } else {
+            ExportLanguage::Hungarian
+        }
+    }
+
+    /// Words naming the event type. Class, exam and consultation words are matched as whole
+    /// words of the type part of the summary, deadline words anywhere in it, ignoring case.
+    pub fn kind_words(&self, kind: EventKind) -> &'static [&'static str] {
+        match (self, kind) {
+            (ExportLanguage::Hungarian, EventKind::Class) => &["Tanóra"],
+            (ExportLanguage::Hungarian, EventKind::Exam) => &["Vizsga"],
+            (ExportLanguage::Hungarian, EventKind::Consultation) => &["Konzultáció"],
+            (ExportLanguage::Hungarian, EventKind::Deadline) => {
+                &["határidő", "jelentkezés", "időszak"]
+            }
+            (ExportLanguage::English, EventKind::Class) => &["Class", "Lesson"],
+            (ExportLanguage::English, EventKind::Exam) => &["Exam"],
+            (ExportLanguage::English, EventKind::Consultation) => &["Consultation"],
+            (ExportLanguage::English, EventKind::Deadline) => {
+                &["deadline", "registration", "period"]
+            }
+            (_, EventKind::Other) => &[],
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportLanguage::Hungarian => "magyar",
+            ExportLanguage::English => "angol",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summarytemplate::{summary_kind, SummaryTemplate};
+
+    #[test]
+    fn detects_language_by_majority() {
+        let hungarian = [
+            "Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra",
+            "Őszi szünet",
+        ];
+        let english = [
+            "Analysis I (KMANA1EBNE) - Dr. Kiss Péter - Class",
+            "Physics I (KMFIZ1EBNE) - Nagy Anna - Exam",
+            "Autumn break",
+        ];
+        let templates = SummaryTemplate::builtin();
+        let detect = |summaries: &[&'static str]| {
+            ExportLanguage::detect(summaries.iter().map(|s| summary_kind(s, &templates)))
+        };
+        assert_eq!(detect(&hungarian), ExportLanguage::Hungarian);
+        assert_eq!(detect(&english), ExportLanguage::English);
+        assert_eq!(ExportLanguage::detect([]), ExportLanguage::Hungarian);
+    }
+}
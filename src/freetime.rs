@@ -0,0 +1,74 @@
+use crate::neptunevent::NeptunEvent;
+use chrono::{DateTime, Utc};
+
+/// The breaks between the timed events of a day, from the first start to the last end.
+/// Cancelled events leave their time free.
+pub fn free_time<'a>(
+    events: impl IntoIterator<Item = &'a NeptunEvent>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut spans = events
+        .into_iter()
+        .filter(|event| !event.is_cancelled())
+        .filter_map(NeptunEvent::span)
+        .collect::<Vec<(DateTime<Utc>, DateTime<Utc>)>>();
+    spans.sort();
+    let mut breaks = Vec::new();
+    let mut busy_until: Option<DateTime<Utc>> = None;
+    for (start, end) in spans {
+        if let Some(until) = busy_until.filter(|until| *until < start) {
+            breaks.push((until, start));
+        }
+        busy_until = Some(busy_until.map_or(end, |until| until.max(end)));
+    }
+    breaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exportlanguage::ExportLanguage;
+    use crate::parserules::ParseRules;
+    use icalendar::{Component, Event, EventLike, EventStatus};
+
+    fn event(start: &str, end: &str, status: Option<EventStatus>) -> NeptunEvent {
+        let mut event = Event::new();
+        event
+            .summary("Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Tanóra")
+            .starts(start.parse::<DateTime<Utc>>().unwrap())
+            .ends(end.parse::<DateTime<Utc>>().unwrap());
+        if let Some(status) = status {
+            event.status(status);
+        }
+        let (event, _) = NeptunEvent::parse(
+            &event.done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        event
+    }
+
+    #[test]
+    fn finds_breaks_and_frees_cancelled_time() {
+        let events = [
+            event("2024-11-20T08:00:00Z", "2024-11-20T09:30:00Z", None),
+            event("2024-11-20T09:00:00Z", "2024-11-20T10:00:00Z", None),
+            event(
+                "2024-11-20T10:15:00Z",
+                "2024-11-20T11:45:00Z",
+                Some(EventStatus::Cancelled),
+            ),
+            event(
+                "2024-11-20T12:00:00Z",
+                "2024-11-20T13:30:00Z",
+                Some(EventStatus::Confirmed),
+            ),
+        ];
+        assert!(events[2].is_cancelled() && !events[3].is_cancelled());
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            free_time(&events),
+            [(at("2024-11-20T10:00:00Z"), at("2024-11-20T12:00:00Z"))]
+        );
+        assert!(free_time(&events[..1]).is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+use crate::eventfilter::EventFilter;
+use crate::neptunevent::{EventKind, NeptunEvent};
+use crate::schedule::{get_classes_by_week, week_bounds, weekday_name};
+use crate::weekgrid::{self, DAYS, QUARTERS};
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+
+/// Fill and border of the sessions of a course, light enough to print text on.
+const PALETTE: [(&str, &str); 10] = [
+    ("#cfe2ff", "#3d6fb6"),
+    ("#d1f0d8", "#3c8a52"),
+    ("#ffe3c2", "#c27a2c"),
+    ("#f3d1f0", "#9b4a94"),
+    ("#fff3b0", "#a8921c"),
+    ("#d4f1f4", "#2f8c96"),
+    ("#f8d0d0", "#b04545"),
+    ("#e2dcf7", "#6552a8"),
+    ("#e4ead0", "#6f7f3a"),
+    ("#f0dccb", "#8a5a3a"),
+];
+const CANCELLED: (&str, &str) = ("#eeeeee", "#999999");
+
+const STYLE: &str = "\
+@page { size: A4 landscape; margin: 10mm; }
+* { box-sizing: border-box; }
+html, body { margin: 0; }
+body { font: 8pt/1.25 sans-serif; color: #222; background: #888;
+  -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+.page { width: 277mm; height: 190mm; margin: 10mm auto; padding: 0; background: #fff;
+  display: flex; flex-direction: column; overflow: hidden; }
+@media print { body { background: none; } .page { margin: 0; } }
+h1 { font-size: 13pt; margin: 0 0 3mm; }
+h1 span { font-weight: normal; color: #555; }
+.grid { flex: 1; display: grid; grid-template-columns: 11mm repeat(5, 1fr);
+  grid-template-rows: auto auto 1fr; min-height: 0; }
+.head { text-align: center; font-weight: bold; padding: 1mm 0; border-bottom: 0.3mm solid #444; }
+.head small { font-weight: normal; color: #555; }
+.all-day { padding: 0.5mm; }
+.all-day div { background: #fff3b0; border-left: 0.8mm solid #a8921c; padding: 0.3mm 1mm;
+  margin-bottom: 0.5mm; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+.axis, .day { position: relative; }
+.day { border-left: 0.2mm solid #999; }
+.day:last-child { border-right: 0.2mm solid #999; }
+.axis span { position: absolute; right: 1.5mm; transform: translateY(-50%); color: #555; }
+.hour { position: absolute; left: 0; right: 0; border-top: 0.2mm solid #ccc; }
+.event { position: absolute; overflow: hidden; padding: 0.5mm 1mm; border: 0.2mm solid;
+  border-left-width: 1mm; border-radius: 0.8mm; break-inside: avoid; }
+.event b { display: block; }
+.cancelled { text-decoration: line-through; color: #777; }
+.legend { list-style: none; margin: 3mm 0 0; padding: 0; display: flex; flex-wrap: wrap;
+  gap: 1mm 5mm; }
+.legend span { display: inline-block; width: 3mm; height: 3mm; margin-right: 1mm;
+  border: 0.2mm solid; vertical-align: middle; }
+";
+
+/// Renders the week of `date` as a self-contained HTML page, laid out on the [`weekgrid`] of
+/// the TUI timetable: a column for each weekday, a 07:00-20:00 axis and sessions as tall as
+/// they are long. Each course has a colour of its own, listed under the timetable. The page is
+/// sized to print on a landscape A4 sheet.
+pub fn to_html(events: &[NeptunEvent], date: NaiveDate, tz: &Tz) -> String {
+    let (monday, _) = week_bounds(&date);
+    let friday = monday + TimeDelta::days(DAYS as i64 - 1);
+    let timed = get_classes_by_week(events, &date, tz, &EventFilter::default())
+        .into_iter()
+        .filter(|event| event.all_day().is_none())
+        .collect::<Vec<&NeptunEvent>>();
+    let columns = weekgrid::columns(timed, tz);
+    let mut courses = BTreeMap::new();
+    for event in columns.iter().flatten() {
+        courses.entry(course(event)).or_insert(event.title());
+    }
+    let courses = courses.into_iter().collect::<Vec<(&str, &str)>>();
+    let color = |event: &NeptunEvent| {
+        if event.is_cancelled() {
+            CANCELLED
+        } else {
+            let index = courses
+                .iter()
+                .position(|(code, _)| *code == course(event))
+                .unwrap_or_default();
+            PALETTE[index % PALETTE.len()]
+        }
+    };
+
+    let range = format!(
+        "{} – {}",
+        monday.format("%Y.%m.%d."),
+        friday.format("%Y.%m.%d.")
+    );
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"hu\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Órarend {range}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n\
+         <main class=\"page\">\n<h1>Órarend <span>{range}</span></h1>\n<div class=\"grid\">\n\
+         <div class=\"head\"></div>\n"
+    );
+    let days = (0..DAYS)
+        .map(|i| monday + TimeDelta::days(i as i64))
+        .collect::<Vec<NaiveDate>>();
+    for day in &days {
+        html.push_str(&format!(
+            "<div class=\"head\">{}<br><small>{}</small></div>\n",
+            weekday_name(day.weekday()),
+            day.format("%m.%d.")
+        ));
+    }
+
+    html.push_str("<div></div>\n");
+    for day in &days {
+        html.push_str("<div class=\"all-day\">");
+        for event in events.iter().filter_map(NeptunEvent::all_day) {
+            if event.covers(day) {
+                html.push_str(&format!("<div>{}</div>", escape(&event.summary)));
+            }
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("<div class=\"axis\">");
+    for hour in (0..=QUARTERS).step_by(4) {
+        html.push_str(&format!(
+            "<span style=\"top:{}\">{:02}:00</span>",
+            percent(hour),
+            (weekgrid::FIRST_QUARTER + hour) / 4
+        ));
+    }
+    html.push_str("</div>\n");
+    for column in &columns {
+        html.push_str("<div class=\"day\">");
+        for hour in (4..QUARTERS).step_by(4) {
+            html.push_str(&format!(
+                "<div class=\"hour\" style=\"top:{}\"></div>",
+                percent(hour)
+            ));
+        }
+        for (event, (lane, lanes)) in column.iter().zip(lanes(column, tz)) {
+            html.push_str(&block(event, lane, lanes, color(event), tz));
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</div>\n<ul class=\"legend\">\n");
+    for (i, (code, title)) in courses.iter().enumerate() {
+        let (fill, border) = PALETTE[i % PALETTE.len()];
+        html.push_str(&format!(
+            "<li><span style=\"background:{fill};border-color:{border}\"></span>{} ({})</li>\n",
+            escape(title),
+            escape(code)
+        ));
+    }
+    html.push_str("</ul>\n</main>\n</body>\n</html>\n");
+    html
+}
+
+/// What the colour of a session goes by: the subject code of a class, so that its lectures
+/// and seminars match, the code of an exam, or the title.
+fn course(event: &NeptunEvent) -> &str {
+    match (event.class(), event.exam()) {
+        (Some(class), _) => class.course_code().subject(),
+        (_, Some(exam)) => exam.code(),
+        _ => event.title(),
+    }
+}
+
+/// Where the block of an event sits on the time axis, at least a quarter tall and inside the
+/// axis, so that an event clamped at 20:00 still shows.
+fn rows(event: &NeptunEvent, tz: &Tz) -> (u8, u8) {
+    let (start, height) = weekgrid::rows(event, tz);
+    (start.min(QUARTERS - 1), height.max(1))
+}
+
+/// The lane of each event of a column and how many lanes its group of overlapping events
+/// needs, so that sessions at the same time stand side by side.
+fn lanes(column: &[&NeptunEvent], tz: &Tz) -> Vec<(usize, usize)> {
+    let rows = column
+        .iter()
+        .map(|event| {
+            let (start, height) = rows(event, tz);
+            (start, start + height)
+        })
+        .collect::<Vec<(u8, u8)>>();
+    let mut placed = vec![(0, 1); column.len()];
+    let mut group = 0;
+    while group < rows.len() {
+        let mut ends: Vec<u8> = Vec::new();
+        let mut group_end = rows[group].1;
+        let mut next = group;
+        while next < rows.len() && (next == group || rows[next].0 < group_end) {
+            let (start, end) = rows[next];
+            let lane = match ends.iter().position(|lane_end| *lane_end <= start) {
+                Some(lane) => lane,
+                None => {
+                    ends.push(0);
+                    ends.len() - 1
+                }
+            };
+            ends[lane] = end;
+            placed[next].0 = lane;
+            group_end = group_end.max(end);
+            next += 1;
+        }
+        for place in &mut placed[group..next] {
+            place.1 = ends.len();
+        }
+        group = next;
+    }
+    placed
+}
+
+fn block(event: &NeptunEvent, lane: usize, lanes: usize, color: (&str, &str), tz: &Tz) -> String {
+    let (start, height) = rows(event, tz);
+    let width = 100.0 / lanes as f64;
+    let [_, _, time, location, _] = event.string_array(tz);
+    let kind = match event.class() {
+        Some(class) if event.kind() == EventKind::Class => {
+            let label = class.class_type().label();
+            match class.course_code().group() {
+                Some(group) => format!("{} {}", group, label),
+                None => label.to_owned(),
+            }
+        }
+        _ => event.kind().label().to_lowercase(),
+    };
+    let details = [time, location, kind]
+        .into_iter()
+        .filter(|detail| !detail.is_empty())
+        .map(|detail| escape(&detail))
+        .collect::<Vec<String>>()
+        .join("<br>");
+    let (class, note) = if event.is_cancelled() {
+        (" cancelled", " (elmaradt)")
+    } else {
+        ("", "")
+    };
+    format!(
+        "<div class=\"event{class}\" style=\"top:{};height:{};left:{:.3}%;width:{:.3}%;\
+         background:{};border-color:{}\"><b>{}{note}</b>{details}</div>",
+        percent(start),
+        percent(height),
+        width * lane as f64,
+        width,
+        color.0,
+        color.1,
+        escape(event.title())
+    )
+}
+
+/// A position on the time axis, in percent of its height.
+fn percent(quarters: u8) -> String {
+    format!("{:.3}%", f64::from(quarters) * 100.0 / f64::from(QUARTERS))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::get_classes;
+    use crate::exportlanguage::ExportLanguage;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+    use crate::parserules::ParseRules;
+    use chrono::{DateTime, Utc};
+    use icalendar::{Component, EventLike};
+
+    #[test]
+    fn renders_a_printable_week() {
+        let events = get_classes(
+            &include_str!("../fixtures/recurring.ics").parse().unwrap(),
+            &ParseRules::builtin(),
+        )
+        .events;
+        let date = NaiveDate::from_ymd_opt(2024, 9, 11).unwrap();
+        let html = to_html(&events, date, &DEFAULT_TIMEZONE);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("size: A4 landscape"));
+        assert!(!html.contains("<link") && !html.contains("<script") && !html.contains("src="));
+        assert!(html.contains("<title>Órarend 2024.09.09. – 2024.09.13.</title>"));
+        assert!(html.contains(">Hétfő<br>") && html.contains(">Péntek<br>"));
+        assert!(html.contains(">07:00</span>") && html.contains(">20:00</span>"));
+
+        // Wednesday 08:15-09:45 is 5 quarters below 07:00 and 6 quarters tall.
+        let lecture = html
+            .split("<div class=\"event")
+            .find(|block| block.contains("Analízis I"))
+            .unwrap();
+        assert!(lecture.contains("top:9.615%;height:11.538%;left:0.000%;width:100.000%"));
+        assert!(lecture.contains("08:15 - 09:45<br>BA.1.13"));
+
+        // The lab on Monday and Wednesday shares its colour, which is not the lecture's.
+        let colours = html
+            .split("<div class=\"event")
+            .skip(1)
+            .map(|block| {
+                let start = block.find("background:").unwrap();
+                (block.contains("Fizika I"), &block[start..start + 18])
+            })
+            .collect::<Vec<(bool, &str)>>();
+        assert_eq!(colours.len(), 3);
+        let lab = colours.iter().find(|(fizika, _)| *fizika).unwrap().1;
+        for (fizika, colour) in &colours {
+            assert_eq!(*fizika, *colour == lab);
+        }
+        let legend = &html[html.find("<ul class=\"legend\">").unwrap()..];
+        assert!(
+            legend.contains("Analízis I (KMANA1EBNE)") && legend.contains("Fizika I (KMFIZ1EBNE)")
+        );
+
+        // Starting at 20:00, the end of the axis, the block is still a quarter tall.
+        let (late, _) = NeptunEvent::parse(
+            &icalendar::Event::new()
+                .summary("Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra")
+                .starts("2024-09-11T18:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .ends("2024-09-11T19:30:00Z".parse::<DateTime<Utc>>().unwrap())
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        let html = to_html(&[late], date, &DEFAULT_TIMEZONE);
+        assert!(html.contains("top:98.077%;height:1.923%;"));
+    }
+}
@@ -0,0 +1,670 @@
+use crate::coursecode::ClassType;
+use crate::neptunevent::{EventKind, NeptunEvent};
+use crate::teacher::join_names;
+use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::{OffsetComponents, OffsetName, Tz, TzOffset};
+use icalendar::{Calendar, CalendarDateTime, Component, Event, EventLike, Property};
+use std::collections::BTreeMap;
+
+const PRODUCT_ID: &str = "-//neptunics//neptunics//HU";
+const CALENDAR_NAME: &str = "Neptun órarend";
+
+/// Writes the classes and exams among `events` as an iCalendar file, with their times in
+/// `tz` and a VTIMEZONE describing it. Other events have no place in a timetable and are left
+/// out. The weekly sessions of a class are folded into one recurring event with an RRULE.
+///
+/// Every event has a UID that stays the same from one export to the next, so importing a newer
+/// export updates the events imported before instead of adding them again.
+pub fn to_ics(events: &[&NeptunEvent], tz: &Tz) -> String {
+    let exported = events
+        .iter()
+        .copied()
+        .filter(|event| event.class().is_some() || event.exam().is_some())
+        .filter_map(|event| Some((event, event.span()?)))
+        .collect::<Vec<Session>>();
+
+    let mut calendar = Calendar::empty();
+    calendar
+        .append_property(Property::new("VERSION", "2.0"))
+        .append_property(Property::new("PRODID", PRODUCT_ID))
+        .append_property(Property::new("CALSCALE", "GREGORIAN"))
+        .append_property(Property::new("METHOD", "PUBLISH"))
+        .append_property(Property::new("X-WR-CALNAME", CALENDAR_NAME))
+        .append_property(Property::new("X-WR-TIMEZONE", tz.name()));
+    let first = exported.iter().map(|(_, (start, _))| *start).min();
+    let last = exported.iter().map(|(_, (_, end))| *end).max();
+    let (series, single) = fold(exported, tz);
+    for series in &series {
+        calendar.push(series.vevent(tz));
+        for (recurrence_id, session) in &series.overrides {
+            let mut vevent = vevent(&series.uid, *session, tz);
+            calendar.push(
+                vevent
+                    .append_property(zoned_property("RECURRENCE-ID", *recurrence_id, tz))
+                    .done(),
+            );
+        }
+    }
+    for session in single {
+        calendar.push(vevent(&uid(session.0, session.1 .0), session, tz));
+    }
+    let mut ics = calendar.to_string();
+    // icalendar has no VTIMEZONE, and would give any other component a UID and a DTSTAMP, so
+    // the timezone goes in as text ahead of the events.
+    if let (Some(first), Some(last)) = (first, last) {
+        let at = ics.find("BEGIN:VEVENT").unwrap_or(ics.len());
+        ics.insert_str(at, &vtimezone(tz, first, last));
+    }
+    ics
+}
+
+/// A class or an exam with its start and end.
+type Session<'a> = (&'a NeptunEvent, (DateTime<Utc>, DateTime<Utc>));
+
+/// The sessions of a course group on the same weekday and at the same time, written as one
+/// event repeating weekly from the first to the last. A week without a session is excluded,
+/// unless the course group has a session elsewhen that week and nowhere else to go, which then
+/// replaces that week of the series as an override. A cancelled session, or one in another room
+/// or with another description than the series, is an override as well.
+struct Series<'a> {
+    uid: String,
+    sessions: Vec<Session<'a>>,
+    excluded: Vec<DateTime<Utc>>,
+    overrides: Vec<(DateTime<Utc>, Session<'a>)>,
+}
+
+/// What the sessions of a series share, with the weekday counted from Monday and the start
+/// local.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct SeriesKey {
+    code: String,
+    weekday: u32,
+    start: NaiveTime,
+}
+
+impl SeriesKey {
+    /// Classes only, consultations and exams are one-offs.
+    fn of(session: &Session, tz: &Tz) -> Option<SeriesKey> {
+        let (event, (start, _)) = session;
+        let class = event.class().filter(|_| event.kind() == EventKind::Class)?;
+        let start = start.with_timezone(tz);
+        Some(SeriesKey {
+            code: class.code().to_owned(),
+            weekday: start.weekday().num_days_from_monday(),
+            start: start.time(),
+        })
+    }
+
+    /// Made of the slot of the class alone, so that the series keeps its UID through a room
+    /// change, an edited description or a cancelled week, however many sessions it has left.
+    fn uid(&self) -> String {
+        let weekday = Weekday::try_from(self.weekday as u8).map_or("", weekday_code);
+        format!(
+            "{}-{}{}@neptunics",
+            self.code,
+            weekday,
+            self.start.format("%H%M")
+        )
+    }
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// Splits `sessions` into series and the sessions left on their own, both in order of start.
+fn fold<'a>(sessions: Vec<Session<'a>>, tz: &Tz) -> (Vec<Series<'a>>, Vec<Session<'a>>) {
+    let mut groups: BTreeMap<SeriesKey, Vec<Session>> = BTreeMap::new();
+    let mut single = Vec::new();
+    for session in sessions {
+        match SeriesKey::of(&session, tz) {
+            Some(key) => groups.entry(key).or_default().push(session),
+            None => single.push(session),
+        }
+    }
+    let (mut series, mut lone): (Vec<Series>, Vec<Series>) = groups
+        .into_iter()
+        .map(|(key, mut sessions)| {
+            sessions.sort_by_key(|(_, span)| *span);
+            Series {
+                uid: key.uid(),
+                sessions,
+                excluded: Vec::new(),
+                overrides: Vec::new(),
+            }
+        })
+        .partition(|series| series.sessions.len() > 1);
+    for series in &mut series {
+        series.fill_weeks(&mut lone, tz);
+    }
+    series.extend(lone);
+    series.sort_by_key(|series| (series.sessions[0].1, series.uid.clone()));
+    single.sort_by_key(|(event, (start, _))| (*start, uid(event, *start)));
+    (series, single)
+}
+
+/// What an override may change about a session of a series besides its start.
+fn details(session: &Session, tz: &Tz) -> (NaiveTime, String, String) {
+    let (event, (_, end)) = session;
+    let location = event.location().map(|location| location.raw().to_owned());
+    (
+        end.with_timezone(tz).time(),
+        location.unwrap_or_default(),
+        description(event),
+    )
+}
+
+impl<'a> Series<'a> {
+    /// The first session that was held, whose details the recurring event is written with.
+    fn template(&self) -> Session<'a> {
+        *self
+            .sessions
+            .iter()
+            .find(|(event, _)| !event.is_cancelled())
+            .unwrap_or(&self.sessions[0])
+    }
+
+    /// Goes through the weeks from the first session to the last, moving the session of a
+    /// one-session series in `lone` of the same course group into a week without one, or
+    /// excluding the week.
+    fn fill_weeks(&mut self, lone: &mut Vec<Series<'a>>, tz: &Tz) {
+        let template = details(&self.template(), tz);
+        let (first, (first_start, _)) = self.sessions[0];
+        let last_start = self.sessions[self.sessions.len() - 1].1 .0;
+        let code = first.class().map(|class| class.code().to_owned());
+        let local = first_start.with_timezone(tz);
+        let mut week = 0;
+        loop {
+            let date = local.date_naive() + TimeDelta::weeks(week);
+            week += 1;
+            let Some(expected) = tz
+                .from_local_datetime(&date.and_time(local.time()))
+                .earliest()
+                .map(|time| time.with_timezone(&Utc))
+            else {
+                continue;
+            };
+            if expected > last_start {
+                break;
+            }
+            if let Some(session) = self
+                .sessions
+                .iter()
+                .find(|(_, (start, _))| *start == expected)
+            {
+                if session.0.is_cancelled() || details(session, tz) != template {
+                    self.overrides.push((expected, *session));
+                }
+                continue;
+            }
+            let same_week = lone.iter().position(|series| {
+                let (event, (start, _)) = series.sessions[0];
+                event.class().map(|class| class.code()) == code.as_deref()
+                    && start.with_timezone(tz).iso_week() == date.iso_week()
+            });
+            match same_week {
+                Some(index) => {
+                    let moved = lone.remove(index).sessions[0];
+                    self.overrides.push((expected, moved));
+                }
+                None => self.excluded.push(expected),
+            }
+        }
+    }
+
+    /// The recurring event, with the details of the first session that was held. A series of
+    /// one session is written without an RRULE, under the same UID.
+    fn vevent(&self, tz: &Tz) -> Event {
+        let (_, (start, _)) = self.sessions[0];
+        let (template, (template_start, template_end)) = self.template();
+        let end = start + (template_end - template_start);
+        let last_start = self.sessions[self.sessions.len() - 1].1 .0;
+        let mut vevent = vevent(&self.uid, (template, (start, end)), tz);
+        if self.sessions.len() > 1 {
+            vevent.add_property(
+                "RRULE",
+                format!("FREQ=WEEKLY;UNTIL={}", utc_stamp(last_start)),
+            );
+        }
+        if !self.excluded.is_empty() {
+            let dates = self
+                .excluded
+                .iter()
+                .map(|time| local_stamp(*time, tz))
+                .collect::<Vec<String>>()
+                .join(",");
+            vevent.append_multi_property(
+                Property::new("EXDATE", dates)
+                    .add_parameter("TZID", tz.name())
+                    .done(),
+            );
+        }
+        if let Some(last_modified) = self
+            .sessions
+            .iter()
+            .filter_map(|(event, _)| event.properties()?.last_modified())
+            .max()
+        {
+            vevent.last_modified(last_modified);
+        }
+        vevent.done()
+    }
+}
+
+fn local_stamp(time: DateTime<Utc>, tz: &Tz) -> String {
+    time.with_timezone(tz).format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// A property holding a local time in `tz`, such as "RECURRENCE-ID;TZID=Europe/Budapest:…".
+fn zoned_property(name: &str, time: DateTime<Utc>, tz: &Tz) -> Property {
+    Property::new(name, local_stamp(time, tz))
+        .add_parameter("TZID", tz.name())
+        .done()
+}
+
+fn vevent(uid: &str, (event, (start, end)): Session, tz: &Tz) -> Event {
+    let zoned = |time: DateTime<Utc>| CalendarDateTime::WithTimezone {
+        date_time: time.with_timezone(tz).naive_local(),
+        tzid: tz.name().to_owned(),
+    };
+    let mut vevent = Event::new();
+    vevent
+        .uid(uid)
+        .summary(&summary(event))
+        .starts(zoned(start))
+        .ends(zoned(end));
+    if let Some(location) = event
+        .location()
+        .filter(|location| !location.raw().is_empty())
+    {
+        vevent.location(location.raw());
+    }
+    let description = description(event);
+    if !description.is_empty() {
+        vevent.description(&description);
+    }
+    if let Some(status) = event.raw().status {
+        vevent.status(status);
+    }
+    if let Some(last_modified) = event.properties().and_then(|p| p.last_modified()) {
+        vevent.last_modified(last_modified);
+    }
+    vevent.done()
+}
+
+/// The UID of the export, made distinct per session when the export shares it across a
+/// series. An event without one is identified by its course code and start.
+fn uid(event: &NeptunEvent, start: DateTime<Utc>) -> String {
+    let raw = event.raw();
+    let code = match (event.class(), event.exam()) {
+        (Some(class), _) => class.code(),
+        (_, Some(exam)) => exam.code(),
+        _ => event.title(),
+    };
+    match (&raw.uid, raw.recurrence_id) {
+        (Some(uid), Some(recurrence_id)) => format!("{}-{}", uid, utc_stamp(recurrence_id)),
+        (Some(uid), None) => uid.to_owned(),
+        (None, _) => format!("{}-{}@neptunics", code, utc_stamp(start)),
+    }
+}
+
+fn utc_stamp(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The course name with what kind of session it is, "Fizika I (előadás)" instead of the
+/// whole Neptun summary.
+fn summary(event: &NeptunEvent) -> String {
+    let kind = event.kind().label().to_lowercase();
+    if let Some(class) = event.class() {
+        return match class.class_type() {
+            ClassType::Unknown => format!("{} ({})", class.name, kind),
+            class_type => format!("{} ({})", class.name, class_type.label()),
+        };
+    }
+    match event.exam().and_then(|exam| exam.exam_type()) {
+        Some(exam_type) if !exam_type.is_empty() => {
+            format!("{} ({}, {})", event.title(), kind, exam_type.to_lowercase())
+        }
+        _ => format!("{} ({})", event.title(), kind),
+    }
+}
+
+/// The course code, group and people, which no longer fit in the summary.
+fn description(event: &NeptunEvent) -> String {
+    let mut lines = Vec::new();
+    if let Some(class) = event.class() {
+        lines.push(format!("Kurzus: {}", class.course_code().subject()));
+        if let Some(group) = class.course_code().group() {
+            lines.push(format!("Csoport: {}", group));
+        }
+        if !class.teachers().is_empty() {
+            lines.push(format!("Oktató: {}", join_names(class.teachers(), ", ")));
+        }
+    }
+    if let Some(exam) = event.exam() {
+        lines.push(format!("Kurzus: {}", exam.code()));
+        if !exam.examiners().is_empty() {
+            lines.push(format!(
+                "Vizsgáztató: {}",
+                join_names(exam.examiners(), ", ")
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// A VTIMEZONE for `tz` covering the years from `first` to `last`. chrono-tz does not expose
+/// the rules of a zone, so every change of offset in those years is found by stepping through
+/// them and is written as an observance of its own.
+fn vtimezone(tz: &Tz, first: DateTime<Utc>, last: DateTime<Utc>) -> String {
+    let from = Utc
+        .with_ymd_and_hms(first.year(), 1, 1, 0, 0, 0)
+        .single()
+        .unwrap_or(first);
+    let to = Utc
+        .with_ymd_and_hms(last.year() + 1, 1, 1, 0, 0, 0)
+        .single()
+        .unwrap_or(last);
+    let mut text = format!("BEGIN:VTIMEZONE\r\nTZID:{}\r\n", tz.name());
+    let mut offset = offset_at(tz, from);
+    text.push_str(&observance(&offset, &offset, from));
+    let mut time = from;
+    while time < to {
+        let next = time + TimeDelta::hours(1);
+        let next_offset = offset_at(tz, next);
+        if !same_offset(&offset, &next_offset) {
+            let change = transition(tz, time, next);
+            text.push_str(&observance(&offset, &next_offset, change));
+            offset = next_offset;
+        }
+        time = next;
+    }
+    text.push_str("END:VTIMEZONE\r\n");
+    text
+}
+
+fn offset_at(tz: &Tz, time: DateTime<Utc>) -> TzOffset {
+    tz.offset_from_utc_datetime(&time.naive_utc())
+}
+
+fn same_offset(a: &TzOffset, b: &TzOffset) -> bool {
+    a.base_utc_offset() == b.base_utc_offset()
+        && a.dst_offset() == b.dst_offset()
+        && a.abbreviation() == b.abbreviation()
+}
+
+/// The first second at or before `after` with the offset in effect at `after`, given that
+/// `before` has a different one.
+fn transition(tz: &Tz, mut before: DateTime<Utc>, mut after: DateTime<Utc>) -> DateTime<Utc> {
+    let target = offset_at(tz, after);
+    while after - before > TimeDelta::seconds(1) {
+        let middle = before + (after - before) / 2;
+        if same_offset(&offset_at(tz, middle), &target) {
+            after = middle;
+        } else {
+            before = middle;
+        }
+    }
+    after
+}
+
+/// A STANDARD or DAYLIGHT observance starting at `start`, with its local time given in the
+/// offset it replaces as RFC 5545 asks.
+fn observance(from: &TzOffset, to: &TzOffset, start: DateTime<Utc>) -> String {
+    let kind = if to.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    let local: NaiveDateTime = start.naive_utc() + TimeDelta::seconds(total_seconds(from));
+    let mut text = format!(
+        "BEGIN:{kind}\r\nDTSTART:{}\r\nTZOFFSETFROM:{}\r\nTZOFFSETTO:{}\r\n",
+        local.format("%Y%m%dT%H%M%S"),
+        utc_offset(from),
+        utc_offset(to)
+    );
+    if let Some(name) = to.abbreviation() {
+        text.push_str(&format!("TZNAME:{}\r\n", name));
+    }
+    text.push_str(&format!("END:{kind}\r\n"));
+    text
+}
+
+fn total_seconds(offset: &TzOffset) -> i64 {
+    (offset.base_utc_offset() + offset.dst_offset()).num_seconds()
+}
+
+/// An offset as "+0100", with seconds only when it has any.
+fn utc_offset(offset: &TzOffset) -> String {
+    let seconds = total_seconds(offset);
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    match seconds % 60 {
+        0 => format!("{}{:02}{:02}", sign, seconds / 3600, seconds / 60 % 60),
+        rest => format!(
+            "{}{:02}{:02}{:02}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60,
+            rest
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::get_classes;
+    use crate::eventfilter::EventFilter;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+    use crate::parserules::ParseRules;
+    use icalendar::CalendarComponent;
+
+    const CALENDAR: &str = include_str!("../fixtures/neptun_hu.ics");
+
+    fn events(calendar: &str) -> Vec<NeptunEvent> {
+        get_classes(&calendar.parse().unwrap(), &ParseRules::builtin()).events
+    }
+
+    type TestSession = ((DateTime<Utc>, DateTime<Utc>), Option<String>, bool);
+
+    /// The start, end, location and status of every class and exam, to compare a calendar
+    /// with its export read back, where the summaries are no longer those of Neptun.
+    fn sessions(events: &[NeptunEvent], exported: bool) -> Vec<TestSession> {
+        let mut sessions = events
+            .iter()
+            .filter(|event| exported || event.class().is_some() || event.exam().is_some())
+            .filter_map(|event| {
+                Some((
+                    event.span()?,
+                    event.raw().location.clone(),
+                    event.is_cancelled(),
+                ))
+            })
+            .collect::<Vec<TestSession>>();
+        sessions.sort();
+        sessions
+    }
+
+    #[test]
+    fn exports_a_calendar_that_reads_back() {
+        let events = get_classes(&CALENDAR.parse().unwrap(), &ParseRules::builtin()).events;
+        let mut filter = EventFilter::default();
+        filter.toggle_cancelled();
+        let shown = events
+            .iter()
+            .filter(|event| filter.shows(event))
+            .collect::<Vec<&NeptunEvent>>();
+        let ics = to_ics(&shown, &DEFAULT_TIMEZONE);
+        // Only DTSTAMP, the time of the export, differs between two exports.
+        let without_stamp = |ics: &str| {
+            ics.lines()
+                .filter(|line| !line.starts_with("DTSTAMP"))
+                .collect::<Vec<&str>>()
+                .join("\n")
+        };
+        assert_eq!(
+            without_stamp(&ics),
+            without_stamp(&to_ics(&shown, &DEFAULT_TIMEZONE))
+        );
+
+        let calendar = ics.parse::<Calendar>().unwrap();
+        let timezone =
+            &ics[ics.find("BEGIN:VTIMEZONE").unwrap()..ics.find("BEGIN:VEVENT").unwrap()];
+        assert!(timezone.starts_with("BEGIN:VTIMEZONE\r\nTZID:Europe/Budapest\r\n"));
+        assert!(timezone.contains(
+            "BEGIN:DAYLIGHT\r\nDTSTART:20240331T020000\r\nTZOFFSETFROM:+0100\r\n\
+             TZOFFSETTO:+0200\r\nTZNAME:CEST\r\nEND:DAYLIGHT\r\n"
+        ));
+        assert!(timezone.contains("DTSTART:20241027T030000\r\nTZOFFSETFROM:+0200"));
+        assert!(!timezone.contains("UID") && !timezone.contains("DTSTAMP"));
+        assert!(!ics.contains("STATUS:CANCELLED"));
+
+        let exported = calendar
+            .components
+            .iter()
+            .filter_map(CalendarComponent::as_event)
+            .collect::<Vec<&Event>>();
+        let timed = shown
+            .iter()
+            .filter(|event| event.class().is_some() || event.exam().is_some())
+            .count();
+        assert_eq!(exported.len(), timed);
+        let class = events.iter().find_map(NeptunEvent::class).unwrap();
+        let first = exported
+            .iter()
+            .find(|event| event.get_uid() == Some("KMANA1EBNE-MO0815@neptunics"))
+            .unwrap();
+        assert!(first.get_summary().unwrap().starts_with(&class.name));
+        assert_eq!(
+            first.get_start(),
+            Some(
+                CalendarDateTime::WithTimezone {
+                    date_time: class.start_in(&DEFAULT_TIMEZONE).naive_local(),
+                    tzid: "Europe/Budapest".to_owned(),
+                }
+                .into()
+            )
+        );
+        let mut uids = exported
+            .iter()
+            .filter_map(|event| event.get_uid())
+            .collect::<Vec<&str>>();
+        uids.sort();
+        uids.dedup();
+        assert_eq!(uids.len(), exported.len());
+    }
+
+    #[test]
+    fn folds_weekly_sessions() {
+        let events = events(include_str!("../fixtures/recurring.ics"));
+        let ics = to_ics(
+            &events.iter().collect::<Vec<&NeptunEvent>>(),
+            &DEFAULT_TIMEZONE,
+        );
+        let calendar = ics.parse::<Calendar>().unwrap();
+        let exported = calendar
+            .components
+            .iter()
+            .filter_map(CalendarComponent::as_event)
+            .collect::<Vec<&Event>>();
+
+        // The Wednesday lectures are one event over the change to winter time, without the
+        // week the export leaves out. The session on a Thursday is a series of its own.
+        let lecture = exported
+            .iter()
+            .find(|event| event.get_uid() == Some("KMANA1EBNE-WE0815@neptunics"))
+            .unwrap();
+        assert_eq!(
+            lecture.property_value("RRULE"),
+            Some("FREQ=WEEKLY;UNTIL=20241204T071500Z")
+        );
+        assert!(ics.contains("EXDATE;TZID=Europe/Budapest:20241016T081500\r\n"));
+        assert!(exported
+            .iter()
+            .any(|event| event.get_uid() == Some("KMANA1EBNE-TH0815@neptunics")));
+        // Of the lab every other Monday and Wednesday, only the Wednesdays repeat.
+        assert!(ics.contains("UID:KMFIZ1EBNE/L01-WE1200@neptunics\r\n"));
+        assert!(ics.contains("EXDATE;TZID=Europe/Budapest:20240918T120000\r\n"));
+        assert!(exported
+            .iter()
+            .any(|event| event.get_uid() == Some("KMFIZ1EBNE/L01-MO1200@neptunics")));
+        // Consultations are not folded, even when they repeat.
+        let repeating = exported
+            .iter()
+            .filter(|event| event.property_value("RRULE").is_some())
+            .count();
+        assert_eq!(repeating, 2);
+        let reread = get_classes(&calendar, &ParseRules::builtin()).events;
+        assert_eq!(sessions(&reread, true), sessions(&events, false));
+    }
+
+    #[test]
+    fn overrides_moved_and_cancelled_sessions() {
+        let session = |uid: &str, start: &str, end: &str, status: &str| {
+            format!(
+                "BEGIN:VEVENT\r\nUID:{uid}\r\nSUMMARY:Fizika I (GY2 - KMFIZ1EBNE) - Nagy Anna - \
+                 Tanóra\r\nDTSTART;TZID=Europe/Budapest:{start}\r\n\
+                 DTEND;TZID=Europe/Budapest:{end}\r\nLOCATION:BA.2.01\r\n{status}END:VEVENT\r\n"
+            )
+        };
+        let calendar = [
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Neptun//Orarend//HU\r\n".to_owned(),
+            session("a", "20241104T100000", "20241104T113000", ""),
+            session(
+                "b",
+                "20241111T100000",
+                "20241111T113000",
+                "STATUS:CANCELLED\r\n",
+            ),
+            session("c", "20241119T120000", "20241119T133000", ""),
+            session("d", "20241125T100000", "20241125T113000", ""),
+            session("e", "20241209T100000", "20241209T113000", "").replace("BA.2.01", "TG.4.21"),
+            "END:VCALENDAR\r\n".to_owned(),
+        ]
+        .concat();
+        let events = events(&calendar);
+        let ics = to_ics(
+            &events.iter().collect::<Vec<&NeptunEvent>>(),
+            &DEFAULT_TIMEZONE,
+        );
+        let exported = ics.parse::<Calendar>().unwrap();
+        let exported = exported
+            .components
+            .iter()
+            .filter_map(CalendarComponent::as_event)
+            .collect::<Vec<&Event>>();
+
+        assert_eq!(exported.len(), 4);
+        assert!(exported
+            .iter()
+            .all(|event| event.get_uid() == Some("KMFIZ1EBNE/GY2-MO1000@neptunics")));
+        assert_eq!(exported[0].get_location(), Some("BA.2.01"));
+        assert!(ics.contains("RECURRENCE-ID;TZID=Europe/Budapest:20241209T100000\r\n"));
+        assert!(ics.contains("EXDATE;TZID=Europe/Budapest:20241202T100000\r\n"));
+        assert!(ics.contains("RECURRENCE-ID;TZID=Europe/Budapest:20241111T100000\r\n"));
+        assert!(ics.contains("RECURRENCE-ID;TZID=Europe/Budapest:20241118T100000\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Budapest:20241119T120000\r\n"));
+        assert_eq!(exported[0].get_status(), None);
+        assert_eq!(
+            exported[1].get_status(),
+            Some(icalendar::EventStatus::Cancelled)
+        );
+        let reread = get_classes(&ics.parse().unwrap(), &ParseRules::builtin()).events;
+        assert_eq!(sessions(&reread, true), sessions(&events, false));
+
+        // A session left on its own keeps the UID of its series.
+        let alone = to_ics(&[&events[3]], &DEFAULT_TIMEZONE);
+        assert!(alone.contains("UID:KMFIZ1EBNE/GY2-MO1000@neptunics\r\n"));
+        assert!(!alone.contains("RRULE"));
+    }
+}
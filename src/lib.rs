@@ -0,0 +1,66 @@
+//! Parsing and querying Neptun calendar exports, and the widgets of the neptunics TUI.
+//!
+//! - the model: [`neptunevent`], [`neptunclass`], [`neptunexam`], [`neptuncourse`] and the
+//!   parts they are built from
+//! - parsing: [`calendar::parse_calendar`], [`calendar::get_classes`] and [`calendar::merge`],
+//!   configured by [`parserules`] and [`config`]
+//! - queries: [`schedule`] for days and weeks, [`eventfilter`], [`freetime`], and
+//!   [`weekgrid`] for the layout of a weekly timetable
+//! - output: [`report`], [`export`], [`icsexport`] and [`htmlexport`]
+//! - the [`timetable::TimeTable`] widget
+//!
+//! The `core` feature holds the model, parsing and queries and only needs chrono and
+//! icalendar. `export` adds the output formats and `tui` the widget, both are on by default.
+
+#[cfg(feature = "core")]
+pub mod alldayevent;
+#[cfg(feature = "core")]
+pub mod calendar;
+#[cfg(feature = "core")]
+pub mod config;
+#[cfg(feature = "core")]
+pub mod coursecode;
+#[cfg(feature = "core")]
+pub mod eventfilter;
+#[cfg(feature = "core")]
+pub mod eventproperties;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "core")]
+pub mod exportlanguage;
+#[cfg(feature = "core")]
+pub mod freetime;
+#[cfg(feature = "export")]
+pub mod htmlexport;
+#[cfg(feature = "export")]
+pub mod icsexport;
+#[cfg(feature = "core")]
+pub mod location;
+#[cfg(feature = "core")]
+pub mod neptunclass;
+#[cfg(feature = "core")]
+pub mod neptuncourse;
+#[cfg(feature = "core")]
+pub mod neptunevent;
+#[cfg(feature = "core")]
+pub mod neptunexam;
+#[cfg(feature = "core")]
+pub mod parserules;
+#[cfg(feature = "core")]
+pub mod pattern;
+#[cfg(feature = "core")]
+pub mod recurrence;
+#[cfg(feature = "export")]
+pub mod report;
+#[cfg(feature = "core")]
+pub mod schedule;
+#[cfg(feature = "serde")]
+pub mod serialization;
+#[cfg(feature = "core")]
+pub mod summarytemplate;
+#[cfg(feature = "core")]
+pub mod teacher;
+#[cfg(feature = "tui")]
+pub mod timetable;
+#[cfg(feature = "core")]
+pub mod weekgrid;
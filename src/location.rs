@@ -0,0 +1,181 @@
+use crate::neptunclass::NeptunClass;
+use crate::pattern::{Pattern, PatternError, Placeholders};
+use std::collections::BTreeMap;
+use std::fmt;
+
+const PLACEHOLDERS: Placeholders = Placeholders {
+    names: &["building", "floor", "room"],
+    compact: &["building", "floor", "room"],
+    required: &["building", "room"],
+};
+
+/// A named room layout such as `{building}.{floor}.{room}`. `{building}` and `{room}` are
+/// required, none of the parts may contain whitespace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocationPattern {
+    name: String,
+    pattern: Pattern,
+}
+
+impl LocationPattern {
+    pub fn new(name: &str, pattern: &str) -> Result<Self, PatternError> {
+        Ok(LocationPattern {
+            name: name.to_owned(),
+            pattern: Pattern::new(pattern, &PLACEHOLDERS)?,
+        })
+    }
+
+    /// "BA.1.13" and "BA.113".
+    pub fn builtin() -> Vec<LocationPattern> {
+        [
+            ("building-floor-room", "{building}.{floor}.{room}"),
+            ("building-room", "{building}.{room}"),
+        ]
+        .iter()
+        .map(|(name, pattern)| LocationPattern::new(name, pattern).unwrap())
+        .collect()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A location split into building, floor and room by the first pattern that matches it. The
+/// raw text is kept, and is all there is when no pattern matched.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    raw: String,
+    building: Option<String>,
+    floor: Option<String>,
+    room: Option<String>,
+    pattern: Option<String>,
+}
+
+impl Location {
+    pub fn parse(raw: &str, patterns: &[LocationPattern]) -> Location {
+        let raw = raw.trim();
+        let Some((pattern, captures)) = patterns.iter().find_map(|pattern| {
+            pattern
+                .pattern
+                .matches(raw)
+                .map(|captures| (pattern, captures))
+        }) else {
+            return Location {
+                raw: raw.to_owned(),
+                ..Location::default()
+            };
+        };
+        let part = |placeholder| {
+            Some(captures.get(placeholder))
+                .filter(|part| !part.is_empty())
+                .map(str::to_owned)
+        };
+        Location {
+            raw: raw.to_owned(),
+            building: part("building"),
+            floor: part("floor"),
+            room: part("room"),
+            pattern: Some(pattern.name().to_owned()),
+        }
+    }
+
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn building(&self) -> Option<&str> {
+        self.building.as_deref()
+    }
+
+    pub fn floor(&self) -> Option<&str> {
+        self.floor.as_deref()
+    }
+
+    pub fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    /// Name of the location pattern that matched, `None` when only the raw text is known.
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// The classes held in each building, ordered by building. Classes whose location did not
+/// match any pattern are left out.
+pub fn group_by_building<'a>(
+    classes: impl IntoIterator<Item = &'a NeptunClass>,
+) -> Vec<(String, Vec<&'a NeptunClass>)> {
+    let mut buildings: BTreeMap<String, Vec<&NeptunClass>> = BTreeMap::new();
+    for class in classes {
+        if let Some(building) = class.location().building() {
+            buildings
+                .entry(building.to_owned())
+                .or_default()
+                .push(class);
+        }
+    }
+    buildings.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neptunclass::fixtures::class;
+
+    #[test]
+    fn parses_locations() {
+        let patterns = LocationPattern::builtin();
+        let room = Location::parse(" BA.1.13 ", &patterns);
+        assert_eq!(
+            (room.building(), room.floor(), room.room()),
+            (Some("BA"), Some("1"), Some("13"))
+        );
+        assert_eq!(room.to_string(), "BA.1.13");
+        assert_eq!(room.pattern(), Some("building-floor-room"));
+
+        let room = Location::parse("TG.201", &patterns);
+        assert_eq!((room.building(), room.floor()), (Some("TG"), None));
+
+        let online = Location::parse("Online (Teams)", &patterns);
+        assert_eq!((online.raw(), online.pattern()), ("Online (Teams)", None));
+
+        let custom = LocationPattern::new("obuda", "{building}-{floor}{room}").unwrap_err();
+        assert_eq!(custom, PatternError::Adjacent);
+        let custom = LocationPattern::new("obuda", "{building} épület {room}").unwrap();
+        assert_eq!(
+            Location::parse("F épület 09", &[custom]).building(),
+            Some("F")
+        );
+    }
+
+    #[test]
+    fn groups_classes_by_building() {
+        let class = |location| {
+            class(
+                "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Tanóra",
+                "2024-11-20T08:00:00Z",
+                "2024-11-20T09:30:00Z",
+                location,
+            )
+        };
+        let classes = [
+            class("TG.4.21"),
+            class("BA.1.13"),
+            class("BA.2.01"),
+            class(""),
+        ];
+        let buildings = group_by_building(&classes);
+        assert_eq!(buildings.len(), 2);
+        assert_eq!((buildings[0].0.as_str(), buildings[0].1.len()), ("BA", 2));
+        assert_eq!((buildings[1].0.as_str(), buildings[1].1.len()), ("TG", 1));
+    }
+}
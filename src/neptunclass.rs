@@ -1,35 +1,74 @@
-use chrono::{DateTime, Utc};
-use icalendar::DatePerhapsTime;
+use crate::coursecode::{ClassType, CourseCode};
+use crate::eventproperties::EventProperties;
+use crate::location::Location;
+use crate::parserules::ParseRules;
+use crate::summarytemplate::split_summary;
+use crate::teacher::{join_names, normalize_teachers, Teacher};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use icalendar::DatePerhapsTime::DateTime as IcalDateTime;
-use std::cmp::Ordering;
+use icalendar::{CalendarDateTime, Component, DatePerhapsTime, Event, EventLike, EventStatus};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
-#[derive(Clone)]
+/// Neptun exports are made in Hungary, so floating times are read as Budapest time.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Budapest;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    MissingField(&'static str),
+    BadSummary(String),
+    BadDate(&'static str),
+    UnknownTimezone(String),
+    BadRecurrence(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingField(field) => write!(f, "hiányzó mező: {}", field),
+            ParseError::BadSummary(summary) => {
+                write!(f, "nem értelmezhető összefoglaló: \"{}\"", summary)
+            }
+            ParseError::BadDate(field) => write!(f, "nem értelmezhető időpont: {}", field),
+            ParseError::UnknownTimezone(tzid) => write!(f, "ismeretlen időzóna: {}", tzid),
+            ParseError::BadRecurrence(rule) => {
+                write!(f, "nem kezelt ismétlődési szabály: {}", rule)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NeptunClass {
     pub name: String,
     code: String,
-    teachers: Vec<String>,
+    course_code: CourseCode,
+    teachers: Vec<Teacher>,
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
-    location: String,
+    location: Location,
+    timezone: Tz,
+    template: String,
+    properties: EventProperties,
 }
 
-impl Ord for NeptunClass {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.start.cmp(&other.start)
-    }
-}
-
-impl PartialOrd for NeptunClass {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/// Identifies one session of a course: the UID when the export has one, with the
+/// RECURRENCE-ID when the session is one of a series, the course code and start time otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OccurrenceKey {
+    Uid(String),
+    Recurrence(String, DateTime<Utc>),
+    CodeStart(String, DateTime<Utc>),
 }
 
 impl PartialEq for NeptunClass {
     fn eq(&self, other: &Self) -> bool {
-        self.code == other.code
+        self.occurrence_key() == other.occurrence_key()
     }
 }
 
@@ -37,71 +76,157 @@ impl Eq for NeptunClass {}
 
 impl Hash for NeptunClass {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.code.to_string().hash(state);
+        self.occurrence_key().hash(state);
     }
 }
 
 impl NeptunClass {
-    pub fn new(
-        summary: String,
+    pub fn try_new(
+        summary: &str,
         perhaps_start: DatePerhapsTime,
         perhaps_end: DatePerhapsTime,
-        location: String,
-    ) -> Self {
-        let name_and_the_rest: Vec<&str> = summary.split(" ( - ").collect::<Vec<&str>>();
-        let name = name_and_the_rest[0];
-        let code_and_the_rest: Vec<&str> =
-            name_and_the_rest[1].split(") - ").collect::<Vec<&str>>();
-        let code = code_and_the_rest[0];
-        let teachers: Vec<String> = code_and_the_rest[1]
-            .split(" - ")
-            .next()
-            .expect("Failed to parse NeptunClass")
-            .split(";")
-            .map(|s| s.to_owned())
-            .collect::<Vec<String>>();
-        let start: DateTime<Utc> = match perhaps_start {
-            IcalDateTime(idt) => match idt.try_into_utc() {
-                Some(dt) => dt,
-                _ => DateTime::<Utc>::MIN_UTC,
-            },
-            _ => DateTime::<Utc>::MIN_UTC,
-        };
-        let end: DateTime<Utc> = match perhaps_end {
-            IcalDateTime(idt) => match idt.try_into_utc() {
-                Some(dt) => dt,
-                _ => DateTime::<Utc>::MIN_UTC,
-            },
-            _ => DateTime::<Utc>::MIN_UTC,
-        };
-        NeptunClass {
-            name: name.to_string(),
-            code: code.to_string(),
-            teachers,
+        location: &str,
+        rules: &ParseRules,
+    ) -> Result<Self, ParseError> {
+        let parts = split_summary(summary, &rules.templates)?;
+        let (start, timezone) = to_utc(perhaps_start, "DTSTART")?;
+        let (end, _) = to_utc(perhaps_end, "DTEND")?;
+        let course_code = CourseCode::parse(parts.code, parts.group);
+        Ok(NeptunClass {
+            name: parts.name.to_string(),
+            code: course_code.to_string(),
+            course_code,
+            teachers: normalize_teachers(&parts.teachers),
             start,
             end,
-            location,
+            location: Location::parse(location, &rules.locations),
+            timezone,
+            template: parts.template,
+            properties: EventProperties::default(),
+        })
+    }
+
+    /// Parses the event and keeps the rest of its properties next to the parsed fields.
+    pub fn from_event(event: &Event, rules: &ParseRules) -> Result<Self, ParseError> {
+        let parsed = NeptunClass::try_new(
+            event
+                .get_summary()
+                .ok_or(ParseError::MissingField("SUMMARY"))?,
+            event
+                .get_start()
+                .ok_or(ParseError::MissingField("DTSTART"))?,
+            event.get_end().ok_or(ParseError::MissingField("DTEND"))?,
+            event.get_location().unwrap_or_default(),
+            rules,
+        )?;
+        Ok(NeptunClass {
+            properties: EventProperties::from(event),
+            ..parsed
+        })
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// The code split into subject code, course group and class type.
+    pub fn course_code(&self) -> &CourseCode {
+        &self.course_code
+    }
+
+    pub fn class_type(&self) -> ClassType {
+        self.course_code.class_type()
+    }
+
+    pub fn occurrence_key(&self) -> OccurrenceKey {
+        match (self.uid(), self.properties.recurrence_id()) {
+            (Some(uid), Some(recurrence_id)) => {
+                OccurrenceKey::Recurrence(uid.to_owned(), recurrence_id)
+            }
+            (Some(uid), None) => OccurrenceKey::Uid(uid.to_owned()),
+            (None, _) => OccurrenceKey::CodeStart(self.code.to_owned(), self.start),
         }
     }
 
-    pub fn string_array(&self) -> [String; 5] {
+    /// The location split into building, floor and room, or only the raw text.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// The teachers without titles, each listed once.
+    pub fn teachers(&self) -> &[Teacher] {
+        &self.teachers
+    }
+
+    /// UID, description, status and the other properties kept from the export.
+    pub fn properties(&self) -> &EventProperties {
+        &self.properties
+    }
+
+    pub fn uid(&self) -> Option<&str> {
+        self.properties.uid()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.properties.description()
+    }
+
+    pub fn status(&self) -> Option<EventStatus> {
+        self.properties.status()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status() == Some(EventStatus::Cancelled)
+    }
+
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.properties.last_modified()
+    }
+
+    pub fn categories(&self) -> &[String] {
+        self.properties.categories()
+    }
+
+    pub fn x_properties(&self) -> &BTreeMap<String, String> {
+        self.properties.x_properties()
+    }
+
+    /// Name of the summary template the event was parsed with.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// The timezone the event was exported in.
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    pub fn start_in(&self, tz: &Tz) -> DateTime<Tz> {
+        self.start.with_timezone(tz)
+    }
+
+    pub fn end_in(&self, tz: &Tz) -> DateTime<Tz> {
+        self.end.with_timezone(tz)
+    }
+
+    pub fn string_array(&self, tz: &Tz) -> [String; 5] {
         [
             self.name.to_owned(),
             self.code.to_owned(),
             format!(
                 "{} - {}",
-                self.start.naive_local().time().format("%H:%M"),
-                self.end.naive_local().time().format("%H:%M")
+                self.start_in(tz).format("%H:%M"),
+                self.end_in(tz).format("%H:%M")
             ),
-            self.location.to_owned(),
-            self.teachers.join(";"),
+            self.location.to_string(),
+            join_names(&self.teachers, ";"),
         ]
     }
 }
 
 impl fmt::Display for NeptunClass {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let string_array: [String; 5] = self.string_array();
+        let string_array: [String; 5] = self.string_array(&self.timezone);
         let mut disp_str = (0..self.name.len()).map(|_| "=").collect::<String>();
         disp_str.push('\n');
         disp_str.push_str(string_array.join("\n").as_str());
@@ -110,3 +235,128 @@ impl fmt::Display for NeptunClass {
         write!(f, "{}", disp_str)
     }
 }
+
+pub(crate) fn to_utc(
+    perhaps_time: DatePerhapsTime,
+    field: &'static str,
+) -> Result<(DateTime<Utc>, Tz), ParseError> {
+    let (naive, tz) = match perhaps_time {
+        IcalDateTime(CalendarDateTime::Utc(dt)) => return Ok((dt, Tz::UTC)),
+        IcalDateTime(CalendarDateTime::Floating(naive)) => (naive, DEFAULT_TIMEZONE),
+        IcalDateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => (
+            date_time,
+            tzid.parse::<Tz>()
+                .map_err(|_| ParseError::UnknownTimezone(tzid))?,
+        ),
+        _ => return Err(ParseError::BadDate(field)),
+    };
+    Ok((localize(naive, &tz).ok_or(ParseError::BadDate(field))?, tz))
+}
+
+/// Times repeated by the autumn DST switch resolve to the first occurrence, times skipped
+/// by the spring one are rejected.
+fn localize(naive: NaiveDateTime, tz: &Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Classes and times for the tests of the modules built on the model.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::NeptunClass;
+    use crate::parserules::ParseRules;
+    use icalendar::{CalendarDateTime, DatePerhapsTime};
+
+    /// A DTSTART or DTEND in UTC, such as "2024-11-20T08:00:00Z".
+    pub fn utc(time: &str) -> DatePerhapsTime {
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(time.parse().unwrap()))
+    }
+
+    /// A class parsed with the built-in rules, which has to parse.
+    pub fn class(summary: &str, start: &str, end: &str, location: &str) -> NeptunClass {
+        NeptunClass::try_new(
+            summary,
+            utc(start),
+            utc(end),
+            location,
+            &ParseRules::builtin(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Timelike};
+
+    fn budapest(y: i32, m: u32, d: u32, h: u32, min: u32) -> DatePerhapsTime {
+        let naive = NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap();
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone {
+            date_time: naive,
+            tzid: "Europe/Budapest".to_owned(),
+        })
+    }
+
+    #[test]
+    fn class_times_survive_the_dst_switch() {
+        let summary = "Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra";
+        let rules = ParseRules::builtin();
+        let winter = NeptunClass::try_new(
+            summary,
+            budapest(2025, 3, 28, 10, 0),
+            budapest(2025, 3, 28, 11, 30),
+            "BA.1.13",
+            &rules,
+        )
+        .unwrap();
+        let summer = NeptunClass::try_new(
+            summary,
+            budapest(2025, 4, 4, 10, 0),
+            budapest(2025, 4, 4, 11, 30),
+            "BA.1.13",
+            &rules,
+        )
+        .unwrap();
+
+        assert_eq!(winter.start.hour(), 9);
+        assert_eq!(summer.start.hour(), 8);
+        assert_eq!(winter.timezone(), DEFAULT_TIMEZONE);
+        for class in [&winter, &summer] {
+            assert_eq!(class.string_array(&DEFAULT_TIMEZONE)[2], "10:00 - 11:30");
+        }
+        assert_eq!(summer.string_array(&Tz::UTC)[2], "08:00 - 09:30");
+    }
+
+    #[test]
+    fn rejects_unknown_and_skipped_times() {
+        let summary = "Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra";
+        let rules = ParseRules::builtin();
+        let unknown = DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone {
+            date_time: NaiveDate::from_ymd_opt(2025, 3, 28)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+            tzid: "Mars/Olympus_Mons".to_owned(),
+        });
+        assert_eq!(
+            NeptunClass::try_new(summary, unknown, budapest(2025, 3, 28, 11, 0), "", &rules).err(),
+            Some(ParseError::UnknownTimezone("Mars/Olympus_Mons".to_owned()))
+        );
+        assert_eq!(
+            NeptunClass::try_new(
+                summary,
+                budapest(2025, 3, 30, 2, 30),
+                budapest(2025, 3, 30, 4, 0),
+                "",
+                &rules
+            )
+            .err(),
+            Some(ParseError::BadDate("DTSTART"))
+        );
+    }
+}
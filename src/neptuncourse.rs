@@ -0,0 +1,171 @@
+use crate::location::Location;
+use crate::neptunclass::NeptunClass;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// Every session of one course, grouped by course code and ordered by start.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NeptunCourse {
+    code: String,
+    name: String,
+    occurrences: Vec<NeptunClass>,
+}
+
+impl NeptunCourse {
+    /// Groups the classes by course code. The same session listed twice is kept once.
+    pub fn group<'a>(classes: impl IntoIterator<Item = &'a NeptunClass>) -> Vec<NeptunCourse> {
+        let mut courses: BTreeMap<&str, Vec<&NeptunClass>> = BTreeMap::new();
+        for class in classes {
+            courses.entry(class.code()).or_default().push(class);
+        }
+        courses
+            .into_iter()
+            .map(|(code, mut occurrences)| {
+                occurrences.sort_by_key(|class| (class.start, class.occurrence_key()));
+                occurrences.dedup();
+                NeptunCourse {
+                    code: code.to_owned(),
+                    name: occurrences[0].name.to_owned(),
+                    occurrences: occurrences.into_iter().cloned().collect(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn occurrences(&self) -> &[NeptunClass] {
+        &self.occurrences
+    }
+
+    pub fn count(&self) -> usize {
+        self.occurrences.len()
+    }
+
+    pub fn first(&self) -> Option<&NeptunClass> {
+        self.occurrences.first()
+    }
+
+    pub fn last(&self) -> Option<&NeptunClass> {
+        self.occurrences.last()
+    }
+
+    /// The session in progress at `now`, or else the first one after it.
+    pub fn next(&self, now: &DateTime<Utc>) -> Option<&NeptunClass> {
+        self.occurrences.iter().find(|class| class.end > *now)
+    }
+
+    /// The room most sessions are held in, the earliest one on a tie. `None` when no room is
+    /// used more than once.
+    pub fn usual_location(&self) -> Option<&Location> {
+        let mut counts: Vec<(&Location, usize)> = Vec::new();
+        for location in self.occurrences.iter().map(NeptunClass::location) {
+            if location.raw().is_empty() {
+                continue;
+            }
+            match counts
+                .iter_mut()
+                .find(|(seen, _)| seen.raw() == location.raw())
+            {
+                Some((_, count)) => *count += 1,
+                None => counts.push((location, 1)),
+            }
+        }
+        counts
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| *count > 1)
+            .map(|(location, _)| location)
+    }
+
+    /// Whether the session is held somewhere other than the course's usual room.
+    pub fn is_room_change(&self, class: &NeptunClass) -> bool {
+        let location = class.location().raw();
+        !location.is_empty()
+            && self
+                .usual_location()
+                .is_some_and(|usual| usual.raw() != location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neptunclass::fixtures::class;
+    use std::collections::HashSet;
+
+    #[test]
+    fn groups_occurrences_into_courses() {
+        let analysis = "Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra";
+        let physics = "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Tanóra";
+        let classes = [
+            class(analysis, "2024-11-27T08:00:00Z", "2024-11-27T09:30:00Z", ""),
+            class(physics, "2024-11-21T10:00:00Z", "2024-11-21T11:30:00Z", ""),
+            class(analysis, "2024-11-20T08:00:00Z", "2024-11-20T09:30:00Z", ""),
+            class(analysis, "2024-11-20T08:00:00Z", "2024-11-20T09:30:00Z", ""),
+        ];
+        assert_eq!(classes.iter().collect::<HashSet<_>>().len(), 3);
+
+        let courses = NeptunCourse::group(&classes);
+        assert_eq!(courses.len(), 2);
+        let analysis = &courses[0];
+        assert_eq!(
+            (analysis.code(), analysis.name()),
+            ("KMANA1EBNE", "Analízis I")
+        );
+        assert_eq!(analysis.count(), 2);
+        assert_eq!(analysis.first().unwrap().start, classes[2].start);
+        assert_eq!(analysis.last().unwrap().start, classes[0].start);
+
+        let during_first: DateTime<Utc> = "2024-11-20T09:00:00Z".parse().unwrap();
+        let after_last: DateTime<Utc> = "2024-11-28T00:00:00Z".parse().unwrap();
+        assert_eq!(
+            analysis.next(&during_first).unwrap().start,
+            classes[2].start
+        );
+        assert!(analysis.next(&after_last).is_none());
+    }
+
+    #[test]
+    fn warns_about_room_changes() {
+        let physics = "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Tanóra";
+        let classes = [
+            class(
+                physics,
+                "2024-11-07T08:00:00Z",
+                "2024-11-07T09:30:00Z",
+                "BA.1.13",
+            ),
+            class(
+                physics,
+                "2024-11-14T08:00:00Z",
+                "2024-11-14T09:30:00Z",
+                "TG.4.21",
+            ),
+            class(
+                physics,
+                "2024-11-21T08:00:00Z",
+                "2024-11-21T09:30:00Z",
+                "BA.1.13",
+            ),
+            class(physics, "2024-11-28T08:00:00Z", "2024-11-28T09:30:00Z", ""),
+        ];
+        let course = &NeptunCourse::group(&classes)[0];
+        assert_eq!(course.usual_location().unwrap().raw(), "BA.1.13");
+        assert!(!course.is_room_change(&classes[0]));
+        assert!(course.is_room_change(&classes[1]));
+        assert!(!course.is_room_change(&classes[3]));
+
+        let course = &NeptunCourse::group(&classes[..2])[0];
+        assert!(course.usual_location().is_none());
+        assert!(!course.is_room_change(&classes[1]));
+    }
+}
@@ -0,0 +1,427 @@
+use crate::alldayevent::AllDayEvent;
+use crate::eventproperties::EventProperties;
+use crate::exportlanguage::ExportLanguage;
+use crate::location::Location;
+use crate::neptunclass::{to_utc, NeptunClass, OccurrenceKey, ParseError};
+use crate::neptunexam::NeptunExam;
+use crate::parserules::ParseRules;
+use crate::recurrence::{instant, recurrence_id};
+use crate::summarytemplate::summary_kind;
+use crate::teacher::join_names;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use icalendar::{Component, DatePerhapsTime, Event, EventLike, EventStatus};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EventKind {
+    Class,
+    Exam,
+    Consultation,
+    Deadline,
+    Other,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 5] = [
+        EventKind::Class,
+        EventKind::Exam,
+        EventKind::Consultation,
+        EventKind::Deadline,
+        EventKind::Other,
+    ];
+
+    /// Sorts an event by the type part of its summary, see [`summary_kind`].
+    pub fn detect(kind: &str, language: ExportLanguage) -> EventKind {
+        let lowercase = kind.to_lowercase();
+        let names = |candidate| {
+            language.kind_words(candidate).iter().any(|word| {
+                lowercase
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|w| w == word.to_lowercase())
+            })
+        };
+        if names(EventKind::Class) {
+            EventKind::Class
+        } else if language
+            .kind_words(EventKind::Deadline)
+            .iter()
+            .any(|word| lowercase.contains(word))
+        {
+            // Checked before exams so that "Vizsgajelentkezés" and "Exam period" land here.
+            EventKind::Deadline
+        } else if names(EventKind::Exam) {
+            EventKind::Exam
+        } else if names(EventKind::Consultation) {
+            EventKind::Consultation
+        } else {
+            EventKind::Other
+        }
+    }
+
+    /// The snake_case name in the JSON and CSV exports, the same as with serde.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Class => "class",
+            EventKind::Exam => "exam",
+            EventKind::Consultation => "consultation",
+            EventKind::Deadline => "deadline",
+            EventKind::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::Class => "Tanóra",
+            EventKind::Exam => "Vizsga",
+            EventKind::Consultation => "Konzultáció",
+            EventKind::Deadline => "Határidő",
+            EventKind::Other => "Egyéb",
+        }
+    }
+}
+
+/// The VEVENT as it was found in the export, kept next to the parsed model so that an
+/// event can still be shown when its summary is not in the expected shape. `source` is the
+/// calendar file it was loaded from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawEvent {
+    pub uid: Option<String>,
+    pub recurrence_id: Option<DateTime<Utc>>,
+    pub source: Option<String>,
+    pub summary: Option<String>,
+    pub location: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::status"))]
+    pub status: Option<EventStatus>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::date_perhaps_time")
+    )]
+    pub start: Option<DatePerhapsTime>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::date_perhaps_time")
+    )]
+    pub end: Option<DatePerhapsTime>,
+}
+
+impl From<&Event> for RawEvent {
+    fn from(event: &Event) -> Self {
+        RawEvent {
+            uid: event.get_uid().map(|s| s.to_owned()),
+            recurrence_id: recurrence_id(event),
+            source: None,
+            summary: event.get_summary().map(|s| s.to_owned()),
+            location: event.get_location().map(|s| s.to_owned()),
+            status: event.get_status(),
+            start: event.get_start(),
+            end: event.get_end(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum NeptunEvent {
+    Class {
+        raw: RawEvent,
+        class: Option<NeptunClass>,
+    },
+    Exam {
+        raw: RawEvent,
+        exam: Option<NeptunExam>,
+    },
+    Consultation {
+        raw: RawEvent,
+        class: Option<NeptunClass>,
+    },
+    Deadline {
+        raw: RawEvent,
+        all_day: Option<AllDayEvent>,
+    },
+    Other {
+        raw: RawEvent,
+        all_day: Option<AllDayEvent>,
+    },
+}
+
+impl NeptunEvent {
+    /// Sorts the event into its kind and parses it. An event that does not parse is still
+    /// returned, with only its raw part filled in, next to the reason it failed.
+    pub fn parse(
+        event: &Event,
+        language: ExportLanguage,
+        rules: &ParseRules,
+    ) -> (NeptunEvent, Option<ParseError>) {
+        let raw = RawEvent::from(event);
+        let kind = raw.summary.as_deref().map_or(EventKind::Other, |summary| {
+            EventKind::detect(summary_kind(summary, &rules.templates), language)
+        });
+        match kind {
+            EventKind::Class | EventKind::Consultation => {
+                let (class, err) = split_result(NeptunClass::from_event(event, rules));
+                match kind {
+                    EventKind::Class => (NeptunEvent::Class { raw, class }, err),
+                    _ => (NeptunEvent::Consultation { raw, class }, err),
+                }
+            }
+            EventKind::Exam => {
+                let (exam, err) = split_result(NeptunExam::from_event(event, rules));
+                (NeptunEvent::Exam { raw, exam }, err)
+            }
+            EventKind::Deadline | EventKind::Other => {
+                let (all_day, err) = if AllDayEvent::is_all_day(event) {
+                    split_result(AllDayEvent::try_from(event))
+                } else {
+                    (None, None)
+                };
+                match kind {
+                    EventKind::Deadline => (NeptunEvent::Deadline { raw, all_day }, err),
+                    _ => (NeptunEvent::Other { raw, all_day }, err),
+                }
+            }
+        }
+    }
+
+    pub fn kind(&self) -> EventKind {
+        match self {
+            NeptunEvent::Class { .. } => EventKind::Class,
+            NeptunEvent::Exam { .. } => EventKind::Exam,
+            NeptunEvent::Consultation { .. } => EventKind::Consultation,
+            NeptunEvent::Deadline { .. } => EventKind::Deadline,
+            NeptunEvent::Other { .. } => EventKind::Other,
+        }
+    }
+
+    pub fn raw(&self) -> &RawEvent {
+        match self {
+            NeptunEvent::Class { raw, .. }
+            | NeptunEvent::Exam { raw, .. }
+            | NeptunEvent::Consultation { raw, .. }
+            | NeptunEvent::Deadline { raw, .. }
+            | NeptunEvent::Other { raw, .. } => raw,
+        }
+    }
+
+    fn raw_mut(&mut self) -> &mut RawEvent {
+        match self {
+            NeptunEvent::Class { raw, .. }
+            | NeptunEvent::Exam { raw, .. }
+            | NeptunEvent::Consultation { raw, .. }
+            | NeptunEvent::Deadline { raw, .. }
+            | NeptunEvent::Other { raw, .. } => raw,
+        }
+    }
+
+    /// The calendar file the event was loaded from.
+    pub fn source(&self) -> Option<&str> {
+        self.raw().source.as_deref()
+    }
+
+    pub fn set_source(&mut self, source: &str) {
+        self.raw_mut().source = Some(source.to_owned());
+    }
+
+    /// Identifies the event across calendars like [`NeptunClass::occurrence_key`], with the
+    /// title in place of the course code for events that are not classes.
+    pub fn occurrence_key(&self) -> Option<OccurrenceKey> {
+        if let Some(class) = self.class() {
+            return Some(class.occurrence_key());
+        }
+        let raw = self.raw();
+        Some(match (&raw.uid, raw.recurrence_id) {
+            (Some(uid), Some(recurrence_id)) => {
+                OccurrenceKey::Recurrence(uid.to_owned(), recurrence_id)
+            }
+            (Some(uid), None) => OccurrenceKey::Uid(uid.to_owned()),
+            (None, _) => OccurrenceKey::CodeStart(
+                self.title().to_owned(),
+                raw.start.as_ref().and_then(instant)?,
+            ),
+        })
+    }
+
+    pub fn class(&self) -> Option<&NeptunClass> {
+        match self {
+            NeptunEvent::Class { class, .. } | NeptunEvent::Consultation { class, .. } => {
+                class.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn exam(&self) -> Option<&NeptunExam> {
+        match self {
+            NeptunEvent::Exam { exam, .. } => exam.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn all_day(&self) -> Option<&AllDayEvent> {
+        match self {
+            NeptunEvent::Deadline { all_day, .. } | NeptunEvent::Other { all_day, .. } => {
+                all_day.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn properties(&self) -> Option<&EventProperties> {
+        match (self.class(), self.exam()) {
+            (Some(class), _) => Some(class.properties()),
+            (_, Some(exam)) => Some(exam.properties()),
+            _ => None,
+        }
+    }
+
+    /// Whether the export marks the event STATUS:CANCELLED.
+    pub fn is_cancelled(&self) -> bool {
+        self.raw().status == Some(EventStatus::Cancelled)
+    }
+
+    /// Start and end of a timed event, taken from the raw times when it did not parse.
+    pub fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if let Some(class) = self.class() {
+            return Some((class.start, class.end));
+        }
+        if let Some(exam) = self.exam() {
+            return Some((exam.start, exam.end));
+        }
+        let raw = self.raw();
+        let (start, _) = to_utc(raw.start.clone()?, "DTSTART").ok()?;
+        let end = raw
+            .end
+            .clone()
+            .and_then(|end| to_utc(end, "DTEND").ok())
+            .map_or(start, |(end, _)| end);
+        Some((start, end))
+    }
+
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.span().map(|(start, _)| start)
+    }
+
+    pub fn title(&self) -> &str {
+        match (self.class(), self.exam()) {
+            (Some(class), _) => &class.name,
+            (_, Some(exam)) => &exam.course,
+            _ => self.raw().summary.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// The parsed location of a class or an exam.
+    pub fn location(&self) -> Option<&Location> {
+        match (self.class(), self.exam()) {
+            (Some(class), _) => Some(class.location()),
+            (_, Some(exam)) => Some(exam.room()),
+            _ => None,
+        }
+    }
+
+    /// Name, code, time, location and people, in the columns of the daily table.
+    pub fn string_array(&self, tz: &Tz) -> [String; 5] {
+        if let Some(class) = self.class() {
+            return class.string_array(tz);
+        }
+        let time = match self.span() {
+            Some((start, end)) => format!(
+                "{} - {}",
+                start.with_timezone(tz).format("%H:%M"),
+                end.with_timezone(tz).format("%H:%M")
+            ),
+            None => String::new(),
+        };
+        match self.exam() {
+            Some(exam) => [
+                exam.course.to_owned(),
+                exam.code().to_owned(),
+                time,
+                exam.room().to_string(),
+                join_names(exam.examiners(), ";"),
+            ],
+            None => [
+                self.title().to_owned(),
+                String::new(),
+                time,
+                self.raw().location.clone().unwrap_or_default(),
+                String::new(),
+            ],
+        }
+    }
+}
+
+fn split_result<T>(result: Result<T, ParseError>) -> (Option<T>, Option<ParseError>) {
+    match result {
+        Ok(parsed) => (Some(parsed), None),
+        Err(err) => (None, Some(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summarytemplate::SummaryTemplate;
+
+    #[test]
+    fn detects_kinds() {
+        let templates = SummaryTemplate::builtin();
+        let kinds = [
+            "Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra",
+            "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Vizsga",
+            "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Konzultáció",
+            "Vizsgajelentkezési időszak",
+            "Őszi szünet",
+        ]
+        .map(|summary| {
+            EventKind::detect(summary_kind(summary, &templates), ExportLanguage::Hungarian)
+        });
+        assert_eq!(kinds, EventKind::ALL);
+
+        let kinds = [
+            "Classical Mechanics (KMMECH1EBNE) - Dr. Kiss Péter - Class",
+            "Physics I (KMFIZ1EBNE) - Nagy Anna - Exam (Written)",
+            "Physics I (KMFIZ1EBNE) - Nagy Anna - Consultation",
+            "Exam registration period",
+            "Classical Mechanics field trip",
+        ]
+        .map(|summary| {
+            EventKind::detect(summary_kind(summary, &templates), ExportLanguage::English)
+        });
+        assert_eq!(kinds, EventKind::ALL);
+
+        let kinds = [
+            "Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - TANÓRA",
+            "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - vizsga",
+        ]
+        .map(|summary| {
+            EventKind::detect(summary_kind(summary, &templates), ExportLanguage::Hungarian)
+        });
+        assert_eq!(kinds, [EventKind::Class, EventKind::Exam]);
+    }
+
+    #[test]
+    fn keeps_raw_fallback() {
+        let event = Event::new()
+            .uid("broken")
+            .summary("Analízis I - Tanóra")
+            .location("BA.1.13")
+            .starts("2024-11-20T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+            .ends("2024-11-20T09:30:00Z".parse::<DateTime<Utc>>().unwrap())
+            .done();
+        let (parsed, err) =
+            NeptunEvent::parse(&event, ExportLanguage::Hungarian, &ParseRules::builtin());
+
+        assert_eq!(parsed.kind(), EventKind::Class);
+        assert!(parsed.class().is_none());
+        assert!(matches!(err, Some(ParseError::BadSummary(_))));
+        assert_eq!(parsed.raw().uid.as_deref(), Some("broken"));
+        assert_eq!(parsed.title(), "Analízis I - Tanóra");
+        assert_eq!(
+            parsed.string_array(&Tz::UTC)[2..4],
+            ["08:00 - 09:30".to_owned(), "BA.1.13".to_owned()]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::eventproperties::EventProperties;
+use crate::location::Location;
+use crate::neptunclass::{to_utc, ParseError};
+use crate::parserules::ParseRules;
+use crate::summarytemplate::split_summary;
+use crate::teacher::{normalize_teachers, Teacher};
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use icalendar::{Component, DatePerhapsTime, Event, EventLike};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NeptunExam {
+    pub course: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    code: String,
+    exam_type: Option<String>,
+    room: Location,
+    examiners: Vec<Teacher>,
+    template: String,
+    properties: EventProperties,
+}
+
+impl NeptunExam {
+    pub fn try_new(
+        summary: &str,
+        perhaps_start: DatePerhapsTime,
+        perhaps_end: DatePerhapsTime,
+        room: &str,
+        rules: &ParseRules,
+    ) -> Result<Self, ParseError> {
+        let parts = split_summary(summary, &rules.templates)?;
+        // The kind reads "Vizsga", "Vizsga - Írásbeli" or "Vizsga (Írásbeli)", and the same
+        // with "Exam" in the English export.
+        let exam_type = parts
+            .kind
+            .trim_start_matches(char::is_alphanumeric)
+            .trim_start_matches(" - ")
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .trim();
+        let (start, _) = to_utc(perhaps_start, "DTSTART")?;
+        let (end, _) = to_utc(perhaps_end, "DTEND")?;
+        Ok(NeptunExam {
+            course: parts.name.to_owned(),
+            start,
+            end,
+            code: parts.code.to_owned(),
+            exam_type: (!exam_type.is_empty()).then(|| exam_type.to_owned()),
+            room: Location::parse(room, &rules.locations),
+            examiners: normalize_teachers(&parts.teachers),
+            template: parts.template,
+            properties: EventProperties::default(),
+        })
+    }
+
+    /// Parses the event and keeps the rest of its properties next to the parsed fields.
+    pub fn from_event(event: &Event, rules: &ParseRules) -> Result<Self, ParseError> {
+        let parsed = NeptunExam::try_new(
+            event
+                .get_summary()
+                .ok_or(ParseError::MissingField("SUMMARY"))?,
+            event
+                .get_start()
+                .ok_or(ParseError::MissingField("DTSTART"))?,
+            event.get_end().ok_or(ParseError::MissingField("DTEND"))?,
+            event.get_location().unwrap_or_default(),
+            rules,
+        )?;
+        Ok(NeptunExam {
+            properties: EventProperties::from(event),
+            ..parsed
+        })
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn exam_type(&self) -> Option<&str> {
+        self.exam_type.as_deref()
+    }
+
+    pub fn room(&self) -> &Location {
+        &self.room
+    }
+
+    pub fn examiners(&self) -> &[Teacher] {
+        &self.examiners
+    }
+
+    /// UID, description, status and the other properties kept from the export.
+    pub fn properties(&self) -> &EventProperties {
+        &self.properties
+    }
+
+    /// Name of the summary template the event was parsed with.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn start_in(&self, tz: &Tz) -> DateTime<Tz> {
+        self.start.with_timezone(tz)
+    }
+
+    /// Time left until the exam starts, `None` once it has started.
+    pub fn time_until(&self, now: &DateTime<Utc>) -> Option<TimeDelta> {
+        (self.start > *now).then(|| self.start - *now)
+    }
+
+    /// Whole calendar days between the day of `previous` and the day of this exam.
+    pub fn days_after(&self, previous: &NeptunExam, tz: &Tz) -> i64 {
+        (self.start_in(tz).date_naive() - previous.start_in(tz).date_naive()).num_days()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neptunclass::fixtures::utc;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+
+    #[test]
+    fn parses_exam_summary() {
+        let exam = NeptunExam::try_new(
+            "Fizika I ( - KMFIZ1EBNE) - Nagy Anna;Dr. Kiss Péter - Vizsga - Írásbeli",
+            utc("2025-01-10T08:00:00Z"),
+            utc("2025-01-10T10:00:00Z"),
+            "BA.1.13",
+            &ParseRules::builtin(),
+        )
+        .unwrap();
+        assert_eq!(exam.course, "Fizika I");
+        assert_eq!(exam.code(), "KMFIZ1EBNE");
+        assert_eq!(exam.exam_type(), Some("Írásbeli"));
+        assert_eq!(
+            exam.examiners()
+                .iter()
+                .map(Teacher::name)
+                .collect::<Vec<_>>(),
+            ["Nagy Anna", "Kiss Péter"]
+        );
+        assert_eq!(exam.room().raw(), "BA.1.13");
+
+        let bare = NeptunExam::try_new(
+            "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Vizsga (Szóbeli)",
+            utc("2025-01-10T08:00:00Z"),
+            utc("2025-01-10T10:00:00Z"),
+            "",
+            &ParseRules::builtin(),
+        )
+        .unwrap();
+        assert_eq!(bare.exam_type(), Some("Szóbeli"));
+    }
+
+    #[test]
+    fn countdown_and_gap() {
+        let summary = "Fizika I ( - KMFIZ1EBNE) - Nagy Anna - Vizsga";
+        let first = NeptunExam::try_new(
+            summary,
+            utc("2025-01-09T23:30:00Z"),
+            utc("2025-01-10T00:00:00Z"),
+            "",
+            &ParseRules::builtin(),
+        )
+        .unwrap();
+        let second = NeptunExam::try_new(
+            summary,
+            utc("2025-01-13T08:00:00Z"),
+            utc("2025-01-13T10:00:00Z"),
+            "",
+            &ParseRules::builtin(),
+        )
+        .unwrap();
+        let now: DateTime<Utc> = "2025-01-08T23:30:00Z".parse().unwrap();
+
+        assert_eq!(first.time_until(&now), Some(TimeDelta::days(1)));
+        assert_eq!(first.time_until(&second.start), None);
+        // 00:30 in Budapest is already the 10th.
+        assert_eq!(second.days_after(&first, &DEFAULT_TIMEZONE), 3);
+        assert_eq!(second.days_after(&first, &Tz::UTC), 4);
+    }
+}
@@ -0,0 +1,18 @@
+use crate::location::LocationPattern;
+use crate::summarytemplate::SummaryTemplate;
+
+/// The summary templates and location patterns events are parsed with, each tried in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseRules {
+    pub templates: Vec<SummaryTemplate>,
+    pub locations: Vec<LocationPattern>,
+}
+
+impl ParseRules {
+    pub fn builtin() -> ParseRules {
+        ParseRules {
+            templates: SummaryTemplate::builtin(),
+            locations: LocationPattern::builtin(),
+        }
+    }
+}
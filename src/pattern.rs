@@ -0,0 +1,201 @@
+use std::fmt;
+
+/// The placeholders a kind of pattern may use.
+pub struct Placeholders {
+    pub names: &'static [&'static str],
+    /// Placeholders whose value never contains whitespace, such as a course code.
+    pub compact: &'static [&'static str],
+    pub required: &'static [&'static str],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternError {
+    UnknownPlaceholder(String),
+    Unclosed,
+    Repeated(&'static str),
+    Missing(&'static str),
+    Adjacent,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::UnknownPlaceholder(name) => {
+                write!(f, "ismeretlen mező a mintában: {{{}}}", name)
+            }
+            PatternError::Unclosed => write!(f, "lezáratlan {{ a mintában"),
+            PatternError::Repeated(name) => {
+                write!(f, "többször szereplő mező a mintában: {{{}}}", name)
+            }
+            PatternError::Missing(name) => write!(f, "hiányzó mező a mintából: {{{}}}", name),
+            PatternError::Adjacent => write!(f, "két mező között nincs elválasztó a mintában"),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// Literal text with `{placeholder}`s in it. A placeholder matches as little non-empty text
+/// as it can.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    source: String,
+    segments: Vec<Segment>,
+    compact: &'static [&'static str],
+}
+
+/// The text each placeholder matched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Captures<'s>(Vec<(&'static str, &'s str)>);
+
+impl<'s> Captures<'s> {
+    /// The trimmed capture of the placeholder, empty when the pattern does not have it.
+    pub fn get(&self, placeholder: &str) -> &'s str {
+        self.0
+            .iter()
+            .find(|(name, _)| *name == placeholder)
+            .map_or("", |(_, value)| value.trim())
+    }
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, placeholders: &Placeholders) -> Result<Self, PatternError> {
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            match rest.split_once('{') {
+                Some((literal, after)) => {
+                    let (placeholder, after) =
+                        after.split_once('}').ok_or(PatternError::Unclosed)?;
+                    let name = placeholders
+                        .names
+                        .iter()
+                        .find(|name| **name == placeholder)
+                        .ok_or_else(|| PatternError::UnknownPlaceholder(placeholder.to_owned()))?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(literal.to_owned()));
+                    } else if matches!(segments.last(), Some(Segment::Placeholder(_))) {
+                        return Err(PatternError::Adjacent);
+                    }
+                    if segments.contains(&Segment::Placeholder(name)) {
+                        return Err(PatternError::Repeated(name));
+                    }
+                    segments.push(Segment::Placeholder(name));
+                    rest = after;
+                }
+                None => {
+                    segments.push(Segment::Literal(rest.to_owned()));
+                    rest = "";
+                }
+            }
+        }
+        for name in placeholders.required {
+            if !segments.contains(&Segment::Placeholder(name)) {
+                return Err(PatternError::Missing(name));
+            }
+        }
+        Ok(Pattern {
+            source: pattern.to_owned(),
+            segments,
+            compact: placeholders.compact,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches<'s>(&self, text: &'s str) -> Option<Captures<'s>> {
+        let mut captures = Captures::default();
+        self.match_segments(&self.segments, text, &mut captures)
+            .then_some(captures)
+    }
+
+    fn accepts(&self, placeholder: &str, value: &str) -> bool {
+        !value.trim().is_empty()
+            && (!self.compact.contains(&placeholder) || !value.contains(char::is_whitespace))
+    }
+
+    /// Backtracks over every place the literal after a placeholder occurs, so that a value may
+    /// contain the separator that follows it.
+    fn match_segments<'s>(
+        &self,
+        segments: &[Segment],
+        text: &'s str,
+        captures: &mut Captures<'s>,
+    ) -> bool {
+        match segments {
+            [] => text.is_empty(),
+            [Segment::Literal(literal), rest @ ..] => text
+                .strip_prefix(literal.as_str())
+                .is_some_and(|text| self.match_segments(rest, text, captures)),
+            [Segment::Placeholder(name)] => {
+                if !self.accepts(name, text) {
+                    return false;
+                }
+                captures.0.push((name, text));
+                true
+            }
+            [Segment::Placeholder(name), Segment::Literal(literal), rest @ ..] => {
+                for (pos, _) in text.match_indices(literal.as_str()) {
+                    if !self.accepts(name, &text[..pos]) {
+                        continue;
+                    }
+                    captures.0.push((name, &text[..pos]));
+                    if self.match_segments(rest, &text[pos + literal.len()..], captures) {
+                        return true;
+                    }
+                    captures.0.pop();
+                }
+                false
+            }
+            [Segment::Placeholder(_), Segment::Placeholder(_), ..] => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLACEHOLDERS: Placeholders = Placeholders {
+        names: &["a", "b"],
+        compact: &["b"],
+        required: &["a"],
+    };
+
+    #[test]
+    fn matches_and_validates() {
+        let pattern = Pattern::new("{a} ({b})", &PLACEHOLDERS).unwrap();
+        let captures = pattern.matches("x (y) (z)").unwrap();
+        assert_eq!((captures.get("a"), captures.get("b")), ("x (y)", "z"));
+        assert!(pattern.matches("x (y z)").is_none());
+
+        assert_eq!(
+            Pattern::new("{a} - {c}", &PLACEHOLDERS),
+            Err(PatternError::UnknownPlaceholder("c".to_owned()))
+        );
+        assert_eq!(
+            Pattern::new("{a}{b}", &PLACEHOLDERS),
+            Err(PatternError::Adjacent)
+        );
+        assert_eq!(
+            Pattern::new("{b} - {b}", &PLACEHOLDERS),
+            Err(PatternError::Repeated("b"))
+        );
+        assert_eq!(
+            Pattern::new("({b})", &PLACEHOLDERS),
+            Err(PatternError::Missing("a"))
+        );
+        assert_eq!(
+            Pattern::new("{a", &PLACEHOLDERS),
+            Err(PatternError::Unclosed)
+        );
+    }
+}
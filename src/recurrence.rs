@@ -0,0 +1,430 @@
+use crate::neptunclass::{to_utc, ParseError};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday};
+use icalendar::{CalendarDateTime, Component, DatePerhapsTime, Event, EventLike, Property};
+use std::str::FromStr;
+
+/// A series is expanded up to a year after its first occurrence, so that a rule without
+/// COUNT or UNTIL still ends.
+const HORIZON: TimeDelta = TimeDelta::days(366);
+const MAX_OCCURRENCES: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The part of an RRULE the expansion understands: FREQ, INTERVAL, COUNT, UNTIL, WKST and
+/// BYDAY without ordinals on daily and weekly rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DatePerhapsTime>,
+    by_day: Vec<Weekday>,
+    week_start: Weekday,
+}
+
+impl Rule {
+    fn parse(value: &str) -> Result<Rule, ParseError> {
+        let bad = || ParseError::BadRecurrence(value.to_owned());
+        let mut frequency = None;
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(bad)?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(bad()),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(bad)?
+                }
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| bad())?),
+                "UNTIL" => rule.until = Some(parse_until(value).ok_or_else(bad)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(weekday)
+                        .collect::<Option<_>>()
+                        .ok_or_else(bad)?
+                }
+                "WKST" => rule.week_start = weekday(value).ok_or_else(bad)?,
+                _ => return Err(bad()),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(bad)?;
+        if !rule.by_day.is_empty()
+            && matches!(rule.frequency, Frequency::Monthly | Frequency::Yearly)
+        {
+            return Err(bad());
+        }
+        Ok(rule)
+    }
+
+    /// The local start of every occurrence, beginning with `start` itself.
+    fn starts(&self, start: &DatePerhapsTime) -> Vec<DatePerhapsTime> {
+        let (first, form) = split(start);
+        let mut starts = vec![start.clone()];
+        let count = self.count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+        let limit = first + HORIZON;
+        for period in 0.. {
+            let step = period * self.interval as i64;
+            let (period_start, dates) = match self.frequency {
+                Frequency::Daily => {
+                    let day = first.date() + TimeDelta::days(step);
+                    let matches = self.by_day.is_empty() || self.by_day.contains(&day.weekday());
+                    (day, if matches { vec![day] } else { Vec::new() })
+                }
+                Frequency::Weekly => {
+                    let week = week_of(first.date(), self.week_start) + TimeDelta::weeks(step);
+                    let mut days = if self.by_day.is_empty() {
+                        vec![first.weekday()]
+                    } else {
+                        self.by_day.clone()
+                    }
+                    .iter()
+                    .map(|day| week + TimeDelta::days(days_from(*day, self.week_start)))
+                    .collect::<Vec<NaiveDate>>();
+                    days.sort();
+                    (week, days)
+                }
+                Frequency::Monthly => {
+                    let months = first.month0() as i64 + step;
+                    let year = first.year() + (months / 12) as i32;
+                    let month = (months % 12) as u32 + 1;
+                    let period_start =
+                        NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(NaiveDate::MAX);
+                    // A day that the month does not have is skipped, not moved.
+                    let day = NaiveDate::from_ymd_opt(year, month, first.day());
+                    (period_start, day.into_iter().collect())
+                }
+                Frequency::Yearly => {
+                    let year = first.year() + step as i32;
+                    let period_start =
+                        NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or(NaiveDate::MAX);
+                    let day = NaiveDate::from_ymd_opt(year, first.month(), first.day());
+                    (period_start, day.into_iter().collect())
+                }
+            };
+            if period_start.and_time(NaiveTime::MIN) > limit {
+                break;
+            }
+            for date in dates {
+                let local = date.and_time(first.time());
+                if local <= first {
+                    continue;
+                }
+                // COUNT includes DTSTART, which is already in `starts`.
+                if starts.len() >= count {
+                    return starts;
+                }
+                let occurrence = join(local, &form);
+                if local > limit || self.is_past_until(local, &occurrence) {
+                    return starts;
+                }
+                starts.push(occurrence);
+            }
+        }
+        starts
+    }
+
+    fn is_past_until(&self, local: NaiveDateTime, occurrence: &DatePerhapsTime) -> bool {
+        match &self.until {
+            None => false,
+            Some(DatePerhapsTime::Date(until)) => local.date() > *until,
+            Some(DatePerhapsTime::DateTime(CalendarDateTime::Floating(until))) => local > *until,
+            Some(until) => instant(occurrence) > instant(until),
+        }
+    }
+}
+
+/// The form a time was written in, kept so that every occurrence is written the same way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Form {
+    Date,
+    Utc,
+    Floating,
+    Zoned(String),
+}
+
+fn split(time: &DatePerhapsTime) -> (NaiveDateTime, Form) {
+    match time {
+        DatePerhapsTime::Date(date) => (date.and_time(NaiveTime::MIN), Form::Date),
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(utc)) => (utc.naive_utc(), Form::Utc),
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(naive)) => (*naive, Form::Floating),
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => {
+            (*date_time, Form::Zoned(tzid.to_owned()))
+        }
+    }
+}
+
+fn join(local: NaiveDateTime, form: &Form) -> DatePerhapsTime {
+    match form {
+        Form::Date => DatePerhapsTime::Date(local.date()),
+        Form::Utc => DatePerhapsTime::DateTime(CalendarDateTime::Utc(local.and_utc())),
+        Form::Floating => DatePerhapsTime::DateTime(CalendarDateTime::Floating(local)),
+        Form::Zoned(tzid) => DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone {
+            date_time: local,
+            tzid: tzid.to_owned(),
+        }),
+    }
+}
+
+/// The moment a time stands for, dates taken at midnight UTC. `None` for an unknown
+/// timezone or a local time skipped by a DST switch.
+pub(crate) fn instant(time: &DatePerhapsTime) -> Option<DateTime<Utc>> {
+    match time {
+        DatePerhapsTime::Date(date) => Some(date.and_time(NaiveTime::MIN).and_utc()),
+        _ => to_utc(time.clone(), "").ok().map(|(utc, _)| utc),
+    }
+}
+
+/// The RECURRENCE-ID of an occurrence of a series, as a moment.
+pub(crate) fn recurrence_id(event: &Event) -> Option<DateTime<Utc>> {
+    instant(&DatePerhapsTime::from_property(
+        event.properties().get("RECURRENCE-ID")?,
+    )?)
+}
+
+fn parse_until(value: &str) -> Option<DatePerhapsTime> {
+    match NaiveDate::parse_from_str(value, "%Y%m%d") {
+        Ok(date) => Some(DatePerhapsTime::Date(date)),
+        Err(_) => CalendarDateTime::from_str(value)
+            .ok()
+            .map(DatePerhapsTime::DateTime),
+    }
+}
+
+fn weekday(value: &str) -> Option<Weekday> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn days_from(day: Weekday, week_start: Weekday) -> i64 {
+    (7 + day.num_days_from_monday() as i64 - week_start.num_days_from_monday() as i64) % 7
+}
+
+fn week_of(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    date - TimeDelta::days(days_from(date.weekday(), week_start))
+}
+
+/// Every value of a repeatable date list property such as EXDATE, which may also hold several
+/// comma separated values.
+fn date_list(event: &Event, name: &str) -> Result<Vec<DatePerhapsTime>, ParseError> {
+    let mut dates = Vec::new();
+    for property in event.multi_properties().get(name).into_iter().flatten() {
+        if property
+            .params()
+            .get("VALUE")
+            .is_some_and(|value| value.value() == "PERIOD")
+        {
+            return Err(ParseError::BadRecurrence(format!(
+                "{}:{}",
+                name,
+                property.value()
+            )));
+        }
+        for value in property.value().split(',') {
+            let mut single = Property::new(name, value.trim());
+            for (key, param) in property.params() {
+                single.add_parameter(key, param.value());
+            }
+            dates.push(DatePerhapsTime::from_property(&single).ok_or_else(|| {
+                ParseError::BadRecurrence(format!("{}:{}", name, property.value()))
+            })?);
+        }
+    }
+    Ok(dates)
+}
+
+/// The occurrences of an event: the event itself when it does not recur, otherwise a copy for
+/// each start of its RRULE and RDATEs that no EXDATE removes, with that start as RECURRENCE-ID.
+/// `overridden` are the RECURRENCE-IDs the export lists as separate events of the series.
+pub fn expand(event: &Event, overridden: &[DateTime<Utc>]) -> Result<Vec<Event>, ParseError> {
+    let rule = event.property_value("RRULE");
+    let extra = date_list(event, "RDATE")?;
+    let (Some(start), true) = (event.get_start(), rule.is_some() || !extra.is_empty()) else {
+        return Ok(vec![event.clone()]);
+    };
+    let mut starts = match rule {
+        Some(rule) => Rule::parse(rule)?.starts(&start),
+        None => vec![start.clone()],
+    };
+    starts.extend(extra);
+    let excluded = date_list(event, "EXDATE")?
+        .iter()
+        .filter_map(instant)
+        .chain(overridden.iter().copied())
+        .collect::<Vec<DateTime<Utc>>>();
+    let mut starts = starts
+        .into_iter()
+        .filter_map(|start| instant(&start).map(|at| (at, start)))
+        .filter(|(at, _)| !excluded.contains(at))
+        .collect::<Vec<(DateTime<Utc>, DatePerhapsTime)>>();
+    starts.sort_by_key(|(at, _)| *at);
+    starts.dedup_by_key(|(at, _)| *at);
+
+    let (first, _) = split(&start);
+    let end = event.get_end().map(|end| {
+        let (last, form) = split(&end);
+        (last - first, form)
+    });
+    Ok(starts
+        .into_iter()
+        .map(|(_, start)| {
+            let mut occurrence = event.clone();
+            occurrence.starts(start.clone());
+            if let Some((length, form)) = &end {
+                let (local, _) = split(&start);
+                occurrence.ends(join(local + *length, form));
+            }
+            occurrence.append_property(start.to_property("RECURRENCE-ID"));
+            occurrence.done()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    use icalendar::{Calendar, CalendarComponent};
+
+    fn events(text: &str) -> Vec<Event> {
+        let cal: Calendar = text.parse().unwrap();
+        cal.components
+            .into_iter()
+            .filter_map(|component| match component {
+                CalendarComponent::Event(event) => Some(event),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn starts(events: &[Event]) -> Vec<DateTime<Utc>> {
+        events
+            .iter()
+            .map(|event| instant(&event.get_start().unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn expands_weekly_series_across_dst() {
+        let series = events(include_str!("../fixtures/recurring.ics"));
+        let occurrences = expand(&series[0], &[]).unwrap();
+        // 13 weeks from September, less the 2024-10-16 EXDATE, plus an RDATE in December.
+        assert_eq!(occurrences.len(), 13);
+        let starts = starts(&occurrences);
+        assert_eq!(
+            starts[0],
+            "2024-09-11T06:15:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(starts.iter().all(|start| start.minute() == 15));
+        // 08:15 in Budapest is 06:15 UTC in summer time and 07:15 UTC in winter time.
+        assert_eq!(starts.iter().filter(|start| start.hour() == 6).count(), 6);
+        assert_eq!(starts.iter().filter(|start| start.hour() == 7).count(), 7);
+        assert!(!starts.contains(&"2024-10-16T06:15:00Z".parse().unwrap()));
+        assert_eq!(
+            starts.last(),
+            Some(&"2024-12-19T07:15:00Z".parse().unwrap())
+        );
+
+        let last = occurrences.last().unwrap();
+        assert_eq!(
+            instant(&last.get_end().unwrap()),
+            Some("2024-12-19T08:45:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            last.property_value("RECURRENCE-ID"),
+            Some("20241219T081500")
+        );
+    }
+
+    #[test]
+    fn expands_rules_within_bounds() {
+        let series = events(include_str!("../fixtures/recurring.ics"));
+        let twice_a_week = expand(&series[1], &[]).unwrap();
+        assert_eq!(
+            starts(&twice_a_week),
+            [
+                "2024-09-09T10:00:00Z",
+                "2024-09-11T10:00:00Z",
+                "2024-09-23T10:00:00Z",
+                "2024-09-25T10:00:00Z",
+            ]
+            .map(|s| s.parse::<DateTime<Utc>>().unwrap())
+        );
+
+        // No COUNT or UNTIL, so the series stops a year after it started.
+        let endless = expand(&series[2], &[]).unwrap();
+        assert_eq!(endless.len(), 12);
+        assert!(starts(&endless)[11] < "2025-10-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+
+        let overridden = "2024-09-16T10:00:00Z".parse().unwrap();
+        assert_eq!(expand(&series[1], &[overridden]).unwrap().len(), 4);
+        let overridden = "2024-09-23T10:00:00Z".parse().unwrap();
+        assert_eq!(expand(&series[1], &[overridden]).unwrap().len(), 3);
+
+        assert_eq!(expand(&series[3], &[]).unwrap(), vec![series[3].clone()]);
+        assert_eq!(
+            expand(&series[4], &[]),
+            Err(ParseError::BadRecurrence(
+                "FREQ=MONTHLY;BYDAY=1MO".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn counts_dtstart_as_the_first_occurrence() {
+        let series = |count: u32| {
+            events(&format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:count@example.hu\r\n\
+                 DTSTART:20240909T100000Z\r\nDTEND:20240909T113000Z\r\n\
+                 RRULE:FREQ=WEEKLY;COUNT={}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+                count
+            ))
+            .remove(0)
+        };
+        assert_eq!(
+            starts(&expand(&series(1), &[]).unwrap()),
+            ["2024-09-09T10:00:00Z".parse::<DateTime<Utc>>().unwrap()]
+        );
+        assert_eq!(
+            starts(&expand(&series(3), &[]).unwrap()),
+            [
+                "2024-09-09T10:00:00Z",
+                "2024-09-16T10:00:00Z",
+                "2024-09-23T10:00:00Z",
+            ]
+            .map(|s| s.parse::<DateTime<Utc>>().unwrap())
+        );
+    }
+}
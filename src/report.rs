@@ -0,0 +1,234 @@
+use crate::eventfilter::EventFilter;
+use crate::export::{to_csv, to_json, to_markdown, Format};
+use crate::neptunevent::NeptunEvent;
+use crate::schedule::{get_classes_by_day, get_classes_by_week, week_bounds, weekday_name};
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+
+const BOLD: &str = "1";
+const CYAN: &str = "36";
+const DARK_GREY: &str = "90";
+
+/// Wraps `text` in an ANSI SGR sequence, without pulling in a terminal library for it.
+fn paint(text: &str, code: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+/// A schedule printed to stdout instead of starting the TUI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Report {
+    Today,
+    Tomorrow,
+    Next,
+    Week,
+}
+
+impl Report {
+    pub fn from_name(name: &str) -> Option<Report> {
+        match name {
+            "today" => Some(Report::Today),
+            "tomorrow" => Some(Report::Tomorrow),
+            "next" => Some(Report::Next),
+            "week" => Some(Report::Week),
+            _ => None,
+        }
+    }
+}
+
+/// Renders `report` for the day `date`, `next` looks for the first event after `now`. ANSI
+/// styles are only used for text when `color` is set, so that the output can be piped.
+pub fn render(
+    report: Report,
+    format: Format,
+    events: &[NeptunEvent],
+    date: NaiveDate,
+    now: DateTime<Utc>,
+    tz: &Tz,
+    color: bool,
+) -> String {
+    let days = select(report, events, date, now, tz);
+    match format {
+        Format::Json => return to_json(&days, tz),
+        Format::Csv => return to_csv(&days, tz),
+        Format::Markdown if !days.is_empty() => return to_markdown(&days, tz),
+        Format::Markdown | Format::Text => {}
+    }
+    if days.is_empty() {
+        return "Nincs következő esemény.\n".to_owned();
+    }
+    let mut out = String::new();
+    for (day, events) in &days {
+        out.push_str(&header(*day, color));
+        for event in events {
+            out.push_str(&line(event, tz, color));
+        }
+        if events.is_empty() {
+            out.push_str("  Nincs esemény.\n");
+        }
+    }
+    out
+}
+
+/// The days listed by `report`, each with its all-day events first and then its timed ones.
+fn select<'a>(
+    report: Report,
+    events: &'a [NeptunEvent],
+    date: NaiveDate,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Vec<(NaiveDate, Vec<&'a NeptunEvent>)> {
+    let filter = EventFilter::default();
+    let with_all_day = |day: NaiveDate, timed: Vec<&'a NeptunEvent>| {
+        let mut listed = events
+            .iter()
+            .filter(|event| event.all_day().is_some_and(|all_day| all_day.covers(&day)))
+            .collect::<Vec<&NeptunEvent>>();
+        listed.extend(timed);
+        (day, listed)
+    };
+    let day = |day: NaiveDate| with_all_day(day, get_classes_by_day(events, &day, tz, &filter));
+    match report {
+        Report::Today => vec![day(date)],
+        Report::Tomorrow => vec![day(date + TimeDelta::days(1))],
+        Report::Week => {
+            let (monday, sunday) = week_bounds(&date);
+            let weekly = get_classes_by_week(events, &date, tz, &filter);
+            monday
+                .iter_days()
+                .take_while(|day| *day <= sunday)
+                .map(|day| {
+                    let timed = weekly
+                        .iter()
+                        .copied()
+                        .filter(|event| local_date(event, tz) == Some(day))
+                        .collect();
+                    with_all_day(day, timed)
+                })
+                // Empty weekends are left out, empty weekdays are worth knowing about.
+                .filter(|(day, listed)| {
+                    !listed.is_empty() || day.weekday().num_days_from_monday() < 5
+                })
+                .collect()
+        }
+        Report::Next => {
+            let mut upcoming = events
+                .iter()
+                .filter(|event| !event.is_cancelled())
+                .filter(|event| event.start().is_some_and(|start| start > now))
+                .collect::<Vec<&NeptunEvent>>();
+            upcoming.sort_by_key(|event| event.start());
+            upcoming
+                .first()
+                .and_then(|event| Some((local_date(event, tz)?, vec![*event])))
+                .into_iter()
+                .collect()
+        }
+    }
+}
+
+fn local_date(event: &NeptunEvent, tz: &Tz) -> Option<NaiveDate> {
+    event
+        .start()
+        .map(|start| start.with_timezone(tz).date_naive())
+}
+
+fn header(day: NaiveDate, color: bool) -> String {
+    let text = format!(
+        "{} {}",
+        day.format("%Y.%m.%d."),
+        weekday_name(day.weekday())
+    );
+    if color {
+        format!("{}\n", paint(&text, BOLD))
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+fn line(event: &NeptunEvent, tz: &Tz, color: bool) -> String {
+    let [name, code, time, location, people] = event.string_array(tz);
+    let title = if code.is_empty() {
+        name
+    } else {
+        format!("{} ({})", name, code)
+    };
+    let mut details = [title, location, people]
+        .into_iter()
+        .filter(|field| !field.is_empty())
+        .collect::<Vec<String>>()
+        .join(" | ");
+    if event.is_cancelled() {
+        details.push_str(" (elmaradt)");
+    }
+    let time = match event.all_day() {
+        Some(_) => format!("{:<13}", "egész nap"),
+        None => format!("{:<13}", time),
+    };
+    match (color, event.is_cancelled()) {
+        (false, _) => format!("  {}  {}\n", time, details),
+        (true, false) => format!("  {}  {}\n", paint(&time, CYAN), details),
+        (true, true) => format!(
+            "  {}  {}\n",
+            paint(&time, DARK_GREY),
+            paint(&details, DARK_GREY)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::get_classes;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+    use crate::parserules::ParseRules;
+
+    const CALENDAR: &str = include_str!("../fixtures/neptun_hu.ics");
+
+    #[test]
+    fn renders_plain_schedules() {
+        let events = get_classes(&CALENDAR.parse().unwrap(), &ParseRules::builtin()).events;
+        let first = events.iter().filter_map(NeptunEvent::start).min().unwrap();
+        let date = first.with_timezone(&DEFAULT_TIMEZONE).date_naive();
+        let render = |report| {
+            render(
+                report,
+                Format::Text,
+                &events,
+                date,
+                first,
+                &DEFAULT_TIMEZONE,
+                false,
+            )
+        };
+
+        let today = render(Report::Today);
+        assert!(today.starts_with(&date.format("%Y.%m.%d.").to_string()));
+        assert!(!today.contains('\u{1b}'));
+        assert!(today.lines().count() > 1);
+
+        let week = render(Report::Week);
+        assert!(week.contains(&today));
+        assert!(week.contains("Hétfő") && week.contains("Péntek"));
+
+        let next = render(Report::Next);
+        assert!(!next.contains("Nincs következő"));
+        assert_eq!(next.lines().count(), 2);
+
+        let formatted = |format, color| {
+            super::render(
+                Report::Week,
+                format,
+                &events,
+                date,
+                first,
+                &DEFAULT_TIMEZONE,
+                color,
+            )
+        };
+        assert!(formatted(Format::Text, true).contains('\u{1b}'));
+        assert!(formatted(Format::Json, true).starts_with("[{"));
+        assert!(!formatted(Format::Json, true).contains('\u{1b}'));
+        assert!(formatted(Format::Markdown, false).starts_with("| Idő | Hétfő"));
+        assert_eq!(Report::from_name("tomorrow"), Some(Report::Tomorrow));
+    }
+}
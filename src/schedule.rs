@@ -0,0 +1,93 @@
+use crate::alldayevent::AllDayEvent;
+use crate::eventfilter::EventFilter;
+use crate::neptunevent::NeptunEvent;
+use chrono::{Datelike, NaiveDate, Weekday};
+use chrono_tz::Tz;
+
+pub fn get_classes_by_day<'a>(
+    events: &'a [NeptunEvent],
+    selected_date: &NaiveDate,
+    tz: &Tz,
+    filter: &EventFilter,
+) -> Vec<&'a NeptunEvent> {
+    let mut daily_classes = events
+        .iter()
+        .filter(|&x| filter.shows(x))
+        .filter(|&x| {
+            x.start()
+                .is_some_and(|start| start.with_timezone(tz).date_naive() == *selected_date)
+        })
+        .collect::<Vec<&NeptunEvent>>();
+
+    daily_classes.sort_by_key(|x| x.start());
+    daily_classes
+}
+
+pub fn week_bounds(selected_date: &NaiveDate) -> (NaiveDate, NaiveDate) {
+    let week = selected_date.iso_week();
+    let mon = NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon)
+        .unwrap_or(NaiveDate::MIN);
+    let sun = NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Sun)
+        .unwrap_or(NaiveDate::MAX);
+    (mon, sun)
+}
+
+pub fn get_classes_by_week<'a>(
+    events: &'a [NeptunEvent],
+    selected_date: &NaiveDate,
+    tz: &Tz,
+    filter: &EventFilter,
+) -> Vec<&'a NeptunEvent> {
+    let (mon, sun) = week_bounds(selected_date);
+    let mut weekly_classes = events
+        .iter()
+        .filter(|&x| filter.shows(x))
+        .filter(|&x| {
+            x.start().is_some_and(|start| {
+                let date = start.with_timezone(tz).date_naive();
+                date >= mon && date <= sun
+            })
+        })
+        .collect::<Vec<&NeptunEvent>>();
+    weekly_classes.sort_by_key(|x| x.start());
+    weekly_classes
+}
+
+pub fn get_all_day_events_by_day<'a>(
+    events: &'a [NeptunEvent],
+    selected_date: &NaiveDate,
+    filter: &EventFilter,
+) -> Vec<&'a AllDayEvent> {
+    events
+        .iter()
+        .filter(|x| filter.shows(x))
+        .filter_map(|x| x.all_day())
+        .filter(|x| x.covers(selected_date))
+        .collect()
+}
+
+pub fn get_all_day_events_by_week<'a>(
+    events: &'a [NeptunEvent],
+    selected_date: &NaiveDate,
+    filter: &EventFilter,
+) -> Vec<&'a AllDayEvent> {
+    let (mon, sun) = week_bounds(selected_date);
+    events
+        .iter()
+        .filter(|x| filter.shows(x))
+        .filter_map(|x| x.all_day())
+        .filter(|x| x.overlaps(&mon, &sun))
+        .collect()
+}
+
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Hétfő",
+        Weekday::Tue => "Kedd",
+        Weekday::Wed => "Szerda",
+        Weekday::Thu => "Csütörtök",
+        Weekday::Fri => "Péntek",
+        Weekday::Sat => "Szombat",
+        Weekday::Sun => "Vasárnap",
+    }
+}
@@ -0,0 +1,222 @@
+//! Serde support for the model, enabled by the `serde` feature.
+//!
+//! The field names are part of the format and only change with a new major version:
+//!
+//! - struct fields keep their Rust names, which are snake_case
+//! - enum values are snake_case strings, such as `"lecture"` for [`ClassType::Lecture`]
+//! - a [`NeptunEvent`] is an object tagged with its kind, `{"kind": "class", "raw": …,
+//!   "class": …}`
+//! - instants are RFC 3339 strings in UTC, dates are `YYYY-MM-DD`, timezones IANA names
+//! - a [`Teacher`] is the name as a string
+//! - an iCalendar STATUS is `"tentative"`, `"confirmed"` or `"cancelled"`
+//! - a raw DTSTART or DTEND is `{"date": …}`, `{"utc": …}`, `{"floating": …}` or
+//!   `{"zoned": {"date_time": …, "tzid": …}}`, with local times as `YYYY-MM-DDTHH:MM:SS`
+//!
+//! [`ClassType::Lecture`]: crate::coursecode::ClassType::Lecture
+//! [`NeptunEvent`]: crate::neptunevent::NeptunEvent
+//! [`Teacher`]: crate::teacher::Teacher
+
+use crate::eventproperties::{status_from_name, status_name};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use icalendar::{CalendarDateTime, DatePerhapsTime, EventStatus};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Time {
+    Date(NaiveDate),
+    Utc(DateTime<Utc>),
+    Floating(NaiveDateTime),
+    Zoned {
+        date_time: NaiveDateTime,
+        tzid: String,
+    },
+}
+
+/// `#[serde(with)]` for an `Option<EventStatus>`.
+pub mod status {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        status: &Option<EventStatus>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        status.map(status_name).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<EventStatus>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|name| {
+                status_from_name(&name).ok_or_else(|| {
+                    serde::de::Error::unknown_variant(
+                        &name,
+                        &["tentative", "confirmed", "cancelled"],
+                    )
+                })
+            })
+            .transpose()
+    }
+}
+
+/// `#[serde(with)]` for an `Option<DatePerhapsTime>`.
+pub mod date_perhaps_time {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<DatePerhapsTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        time.as_ref()
+            .map(|time| match time.clone() {
+                DatePerhapsTime::Date(date) => Time::Date(date),
+                DatePerhapsTime::DateTime(CalendarDateTime::Utc(time)) => Time::Utc(time),
+                DatePerhapsTime::DateTime(CalendarDateTime::Floating(time)) => Time::Floating(time),
+                DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => {
+                    Time::Zoned { date_time, tzid }
+                }
+            })
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DatePerhapsTime>, D::Error> {
+        Ok(
+            Option::<Time>::deserialize(deserializer)?.map(|time| match time {
+                Time::Date(date) => DatePerhapsTime::Date(date),
+                Time::Utc(time) => DatePerhapsTime::DateTime(CalendarDateTime::Utc(time)),
+                Time::Floating(time) => DatePerhapsTime::DateTime(CalendarDateTime::Floating(time)),
+                Time::Zoned { date_time, tzid } => {
+                    DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid })
+                }
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::get_classes;
+    use crate::coursecode::ClassType;
+    use crate::eventproperties::status_name;
+    use crate::neptunclass::NeptunClass;
+    use crate::neptuncourse::NeptunCourse;
+    use crate::neptunevent::{EventKind, NeptunEvent};
+    use crate::neptunexam::NeptunExam;
+    use crate::parserules::ParseRules;
+    use icalendar::EventStatus;
+    use serde_json::{json, Value};
+
+    fn events() -> Vec<NeptunEvent> {
+        let calendar = include_str!("../fixtures/neptun_hu.ics");
+        get_classes(&calendar.parse().unwrap(), &ParseRules::builtin()).events
+    }
+
+    /// Serializing what was deserialized gives back the same JSON.
+    fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+        let json = serde_json::to_value(value).unwrap();
+        let back: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&back).unwrap(), json);
+        back
+    }
+
+    #[test]
+    fn round_trips_the_model() {
+        let events = events();
+        let back = round_trip(&events);
+        assert_eq!(back.len(), events.len());
+        for (event, back) in events.iter().zip(&back) {
+            assert_eq!(event.raw(), back.raw());
+            assert_eq!(event.all_day(), back.all_day());
+            assert_eq!(event.exam(), back.exam());
+        }
+
+        let classes = events
+            .iter()
+            .filter_map(NeptunEvent::class)
+            .cloned()
+            .collect::<Vec<NeptunClass>>();
+        for class in round_trip(&classes).iter().zip(&classes) {
+            assert_eq!(class.0.teachers(), class.1.teachers());
+            assert_eq!(class.0.location(), class.1.location());
+            assert_eq!(class.0.properties(), class.1.properties());
+        }
+        let courses = NeptunCourse::group(&classes);
+        assert_eq!(round_trip(&courses).len(), courses.len());
+        let exams = events
+            .iter()
+            .filter_map(NeptunEvent::exam)
+            .cloned()
+            .collect::<Vec<NeptunExam>>();
+        assert_eq!(round_trip(&exams), exams);
+    }
+
+    #[test]
+    fn keeps_field_names_stable() {
+        let events = events();
+        let class = events.iter().find(|e| e.class().is_some()).unwrap();
+        let value = serde_json::to_value(class).unwrap();
+        assert_eq!(value["kind"], "class");
+        assert_eq!(
+            value["raw"]["start"],
+            json!({"zoned": {"date_time": "2024-11-18T08:15:00", "tzid": "Europe/Budapest"}})
+        );
+        let class = &value["class"];
+        let mut fields = class
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        fields.sort();
+        assert_eq!(
+            fields,
+            [
+                "code",
+                "course_code",
+                "end",
+                "location",
+                "name",
+                "properties",
+                "start",
+                "teachers",
+                "template",
+                "timezone"
+            ]
+        );
+        assert_eq!(class["start"], "2024-11-18T07:15:00Z");
+        assert_eq!(class["timezone"], "Europe/Budapest");
+        assert_eq!(class["teachers"], json!(["Kiss Péter"]));
+        assert_eq!(class["course_code"]["class_type"], "unknown");
+        assert_eq!(
+            class["location"],
+            json!({
+                "raw": "BA.1.13",
+                "building": "BA",
+                "floor": "1",
+                "room": "13",
+                "pattern": "building-floor-room"
+            })
+        );
+        assert_eq!(class["properties"]["status"], Value::Null);
+    }
+
+    #[test]
+    fn names_match_the_json_export() {
+        for class_type in ClassType::ALL {
+            assert_eq!(serde_json::to_value(class_type).unwrap(), class_type.name());
+        }
+        for kind in EventKind::ALL {
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+        }
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Status(#[serde(with = "super::status")] Option<EventStatus>);
+        let cancelled = Status(Some(EventStatus::Cancelled));
+        let json = serde_json::to_value(&cancelled).unwrap();
+        assert_eq!(json, status_name(EventStatus::Cancelled));
+        assert_eq!(serde_json::from_value::<Status>(json).unwrap(), cancelled);
+        assert!(serde_json::from_value::<Status>(json!("CANCELLED")).is_err());
+    }
+}
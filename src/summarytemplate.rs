@@ -0,0 +1,169 @@
+use crate::neptunclass::ParseError;
+use crate::pattern::{Pattern, PatternError, Placeholders};
+
+/// Codes and groups never contain spaces, which lets a template tell
+/// "Fizika (BSc) ( - CODE)" apart.
+const PLACEHOLDERS: Placeholders = Placeholders {
+    names: &["name", "code", "group", "teachers", "type"],
+    compact: &["code", "group"],
+    required: &["name", "code"],
+};
+
+/// A named summary layout such as `{name} ({group} - {code}) - {teachers} - {type}`. Placeholders
+/// match as little text as they can, `{name}` and `{code}` are required, teachers are split
+/// on ";".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SummaryTemplate {
+    name: String,
+    pattern: Pattern,
+}
+
+/// The captures of a matched summary, with the name of the template that matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SummaryParts<'s> {
+    pub name: &'s str,
+    pub code: &'s str,
+    pub group: Option<&'s str>,
+    pub teachers: Vec<String>,
+    pub kind: &'s str,
+    pub template: String,
+}
+
+impl SummaryTemplate {
+    pub fn new(name: &str, pattern: &str) -> Result<Self, PatternError> {
+        Ok(SummaryTemplate {
+            name: name.to_owned(),
+            pattern: Pattern::new(pattern, &PLACEHOLDERS)?,
+        })
+    }
+
+    /// The layouts of the Hungarian and the English Neptun export, with and without a course
+    /// group in front of the code and a type at the end.
+    pub fn builtin() -> Vec<SummaryTemplate> {
+        [
+            (
+                "neptun-group",
+                "{name} ({group} - {code}) - {teachers} - {type}",
+            ),
+            (
+                "neptun-group-no-type",
+                "{name} ({group} - {code}) - {teachers}",
+            ),
+            ("neptun", "{name} ( - {code}) - {teachers} - {type}"),
+            ("neptun-en", "{name} ({code}) - {teachers} - {type}"),
+            ("neptun-no-type", "{name} ( - {code}) - {teachers}"),
+            ("neptun-en-no-type", "{name} ({code}) - {teachers}"),
+        ]
+        .iter()
+        .map(|(name, pattern)| SummaryTemplate::new(name, pattern).unwrap())
+        .collect()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    pub fn matches<'s>(&self, summary: &'s str) -> Option<SummaryParts<'s>> {
+        let captures = self.pattern.matches(summary)?;
+        Some(SummaryParts {
+            name: captures.get("name"),
+            code: captures.get("code"),
+            group: Some(captures.get("group")).filter(|group| !group.is_empty()),
+            teachers: captures
+                .get("teachers")
+                .split(';')
+                .map(|s| s.trim().to_owned())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            kind: captures.get("type"),
+            template: self.name.to_owned(),
+        })
+    }
+}
+
+/// Tries the templates in order and returns the parts from the first one that matches.
+pub fn split_summary<'s>(
+    summary: &'s str,
+    templates: &[SummaryTemplate],
+) -> Result<SummaryParts<'s>, ParseError> {
+    templates
+        .iter()
+        .find_map(|template| template.matches(summary))
+        .ok_or_else(|| ParseError::BadSummary(summary.to_owned()))
+}
+
+/// The event type part of a summary, or the whole summary when it has no course in it.
+pub fn summary_kind<'s>(summary: &'s str, templates: &[SummaryTemplate]) -> &'s str {
+    match split_summary(summary, templates) {
+        Ok(parts) if !parts.kind.is_empty() => parts.kind,
+        _ => summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_templates_match_in_order() {
+        let templates = SummaryTemplate::builtin();
+        let parts = split_summary(
+            "Fizika (BSc) ( - KMFIZ1EBNE) - Nagy Anna; Dr. Kiss Péter - Vizsga - Írásbeli",
+            &templates,
+        )
+        .unwrap();
+        assert_eq!(parts.name, "Fizika (BSc)");
+        assert_eq!(parts.code, "KMFIZ1EBNE");
+        assert_eq!(parts.teachers, ["Nagy Anna", "Dr. Kiss Péter"]);
+        assert_eq!(parts.kind, "Vizsga - Írásbeli");
+        assert_eq!(parts.group, None);
+        assert_eq!(parts.template, "neptun");
+
+        let parts = split_summary(
+            "Fizika I (GY2 - KMFIZ1EBNE) - Nagy Anna - Tanóra",
+            &templates,
+        )
+        .unwrap();
+        assert_eq!((parts.group, parts.code), (Some("GY2"), "KMFIZ1EBNE"));
+        assert_eq!(parts.template, "neptun-group");
+
+        let parts = split_summary("Analysis I (KMANA1EBNE) - Dr. Kiss Péter", &templates).unwrap();
+        assert_eq!((parts.code, parts.kind), ("KMANA1EBNE", ""));
+        assert_eq!(parts.template, "neptun-en-no-type");
+
+        assert_eq!(
+            split_summary("Analízis I - Tanóra", &templates),
+            Err(ParseError::BadSummary("Analízis I - Tanóra".to_owned()))
+        );
+        assert_eq!(summary_kind("Őszi szünet", &templates), "Őszi szünet");
+    }
+
+    #[test]
+    fn custom_template() {
+        let template =
+            SummaryTemplate::new("obuda", "[{code}] {name} / {type} / {teachers}").unwrap();
+        let parts = template
+            .matches("[KMANA1EBNE] Analízis I / Előadás / Dr. Kiss Péter;Nagy Anna")
+            .unwrap();
+        assert_eq!(parts.name, "Analízis I");
+        assert_eq!(parts.kind, "Előadás");
+        assert_eq!(parts.teachers.len(), 2);
+
+        assert_eq!(
+            SummaryTemplate::new("x", "{name} - {room}"),
+            Err(PatternError::UnknownPlaceholder("room".to_owned()))
+        );
+        assert_eq!(
+            SummaryTemplate::new("x", "{name}{code}"),
+            Err(PatternError::Adjacent)
+        );
+        assert_eq!(
+            SummaryTemplate::new("x", "{name} - {teachers}"),
+            Err(PatternError::Missing("code"))
+        );
+    }
+}
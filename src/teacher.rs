@@ -0,0 +1,252 @@
+use crate::config::ConfigError;
+use crate::neptunclass::NeptunClass;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::read_to_string;
+use std::hash::{Hash, Hasher};
+
+/// Academic titles, compared without dots and case.
+const TITLES: [&str; 11] = [
+    "dr", "prof", "habil", "phd", "dsc", "csc", "mba", "msc", "bsc", "med", "univ",
+];
+
+/// A teacher's name with titles and extra whitespace removed. Two teachers are the same when
+/// their names only differ in case.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Teacher {
+    name: String,
+    key: String,
+}
+
+impl Teacher {
+    /// "Dr. habil. Kiss  Péter, PhD" becomes "Kiss Péter". `None` when nothing but titles is left.
+    pub fn parse(raw: &str) -> Option<Teacher> {
+        let name = raw
+            .split_whitespace()
+            .map(|word| word.trim_matches(','))
+            .filter(|word| {
+                let bare = word.replace('.', "").to_lowercase();
+                !bare.is_empty() && !TITLES.contains(&bare.as_str())
+            })
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if name.is_empty() {
+            return None;
+        }
+        Some(Teacher {
+            key: name.to_lowercase(),
+            name,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for Teacher {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Teacher::parse(&name).ok_or_else(|| format!("not a teacher name: {:?}", name))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Teacher> for String {
+    fn from(teacher: Teacher) -> String {
+        teacher.name
+    }
+}
+
+impl PartialEq for Teacher {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Teacher {}
+
+impl Hash for Teacher {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl Ord for Teacher {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl PartialOrd for Teacher {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Teacher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Normalizes a list of teachers, keeping the first spelling of each.
+pub fn normalize_teachers<S: AsRef<str>>(raw: &[S]) -> Vec<Teacher> {
+    let mut teachers: Vec<Teacher> = Vec::new();
+    for teacher in raw.iter().filter_map(|raw| Teacher::parse(raw.as_ref())) {
+        if !teachers.contains(&teacher) {
+            teachers.push(teacher);
+        }
+    }
+    teachers
+}
+
+pub fn join_names(teachers: &[Teacher], separator: &str) -> String {
+    teachers
+        .iter()
+        .map(Teacher::name)
+        .collect::<Vec<&str>>()
+        .join(separator)
+}
+
+/// Every teacher with the classes they teach, ordered by name.
+pub fn group_by_teacher<'a>(
+    classes: impl IntoIterator<Item = &'a NeptunClass>,
+) -> Vec<(Teacher, Vec<&'a NeptunClass>)> {
+    let mut teachers: BTreeMap<Teacher, Vec<&NeptunClass>> = BTreeMap::new();
+    for class in classes {
+        for teacher in class.teachers() {
+            teachers.entry(teacher.clone()).or_default().push(class);
+        }
+    }
+    teachers.into_iter().collect()
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Contact {
+    pub email: Option<String>,
+    pub office_hours: Option<String>,
+}
+
+/// Contacts from a local file with a section per teacher:
+///
+/// ```text
+/// [Dr. Kiss Péter]
+/// email = kiss.peter@example.hu
+/// office_hours = kedd 10:00-12:00, BA.2.14
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ContactBook {
+    contacts: HashMap<String, Contact>,
+}
+
+impl ContactBook {
+    pub fn parse(text: &str, path: &str) -> Result<ContactBook, ConfigError> {
+        let mut book = ContactBook::default();
+        let mut current: Option<String> = None;
+        for (i, line) in text.lines().enumerate() {
+            let syntax = |message: String| ConfigError::Syntax {
+                path: path.to_owned(),
+                line: i + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[') {
+                let teacher = section
+                    .strip_suffix(']')
+                    .and_then(Teacher::parse)
+                    .ok_or_else(|| syntax(format!("hibás oktatónév: {}", line)))?;
+                book.contacts.entry(teacher.key().to_owned()).or_default();
+                current = Some(teacher.key().to_owned());
+                continue;
+            }
+            let contact = current
+                .as_ref()
+                .and_then(|key| book.contacts.get_mut(key))
+                .ok_or_else(|| syntax("hiányzó [oktató] fejléc".to_owned()))?;
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax("hiányzó \"=\"".to_owned()))?;
+            let value = Some(value.trim().to_owned());
+            match key.trim() {
+                "email" => contact.email = value,
+                "office_hours" => contact.office_hours = value,
+                key => return Err(syntax(format!("ismeretlen kulcs: {}", key))),
+            }
+        }
+        Ok(book)
+    }
+
+    pub fn load(path: &str) -> Result<ContactBook, ConfigError> {
+        let text = read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        ContactBook::parse(&text, path)
+    }
+
+    pub fn get(&self, teacher: &Teacher) -> Option<&Contact> {
+        self.contacts.get(teacher.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neptunclass::fixtures::class;
+
+    #[test]
+    fn normalizes_names() {
+        let teachers = normalize_teachers(&[
+            "Dr. habil. Kiss  Péter, PhD",
+            " kiss péter ",
+            "Prof. Dr. Nagy Anna",
+            "Dr.",
+        ]);
+        assert_eq!(
+            teachers.iter().map(Teacher::name).collect::<Vec<_>>(),
+            ["Kiss Péter", "Nagy Anna"]
+        );
+    }
+
+    #[test]
+    fn groups_classes_and_reads_contacts() {
+        let class = |summary| class(summary, "2024-11-20T08:00:00Z", "2024-11-20T09:30:00Z", "");
+        let classes = [
+            class("Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra"),
+            class("Fizika I ( - KMFIZ1EBNE) - KISS PÉTER;Nagy Anna - Tanóra"),
+        ];
+        let directory = group_by_teacher(&classes);
+        assert_eq!(directory.len(), 2);
+        assert_eq!(directory[0].0.name(), "Kiss Péter");
+        assert_eq!(directory[0].1.len(), 2);
+        assert_eq!(directory[1].1.len(), 1);
+
+        let book = ContactBook::parse(
+            "[Dr. Kiss Péter]\nemail = kiss.peter@example.hu\noffice_hours = kedd 10-12\n",
+            "contacts",
+        )
+        .unwrap();
+        let contact = book.get(&directory[0].0).unwrap();
+        assert_eq!(contact.email.as_deref(), Some("kiss.peter@example.hu"));
+        assert_eq!(contact.office_hours.as_deref(), Some("kedd 10-12"));
+        assert!(book.get(&directory[1].0).is_none());
+
+        assert!(matches!(
+            ContactBook::parse("email = x@example.hu\n", "contacts"),
+            Err(ConfigError::Syntax { line: 1, .. })
+        ));
+    }
+}
@@ -1,82 +1,70 @@
-#![allow(dead_code)]
-#![allow(unused_imports)]
-
-use crate::NeptunClass;
-use chrono::{Datelike, NaiveTime, TimeDelta, Timelike};
-use ratatui::layout::Alignment;
-use ratatui::prelude::{Buffer, Frame, Rect};
-use ratatui::style::{Color, Style};
+use crate::alldayevent::AllDayEvent;
+use crate::coursecode::ClassType;
+use crate::neptunclass::DEFAULT_TIMEZONE;
+use crate::neptunevent::{EventKind, NeptunEvent};
+use crate::weekgrid::{self, DAYS, LAST_QUARTER, QUARTERS};
+use chrono::{NaiveDate, NaiveTime, TimeDelta, Timelike};
+use chrono_tz::Tz;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::style::{Color, Style, Stylize};
 use ratatui::symbols;
 use ratatui::text::Line;
 use ratatui::widgets::{
-    canvas::{Canvas, Painter, Rectangle},
+    canvas::{Canvas, Rectangle},
     StatefulWidget, StatefulWidgetRef, Widget, WidgetRef,
 };
 use unicode_segmentation::UnicodeSegmentation;
 
-const HOUR_SEVEN_AS_QUARTERS: u8 = 28;
-const HOUR_TWENTY_AS_QUARTERS: u8 = 76;
+const TIME_AXIS_HEIGHT: f64 = QUARTERS as f64;
+const ALL_DAY_STRIP_HEIGHT: f64 = 4.0;
 
 pub enum TimeTableNavigation {
-    UP,
-    DOWN,
-    LEFT,
-    RIGHT,
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
+#[derive(Default)]
 pub struct TimeTableState {
     pub(crate) selected_day: usize,
     pub(crate) selected_class: Option<usize>,
     pub(crate) index: Option<usize>,
-    distribution: [usize; 5],
+    distribution: [usize; DAYS],
 }
 
-impl Default for TimeTableState {
-    fn default() -> Self {
-        Self {
-            selected_day: 0,
-            selected_class: None,
-            distribution: [0; 5],
-            index: None,
-        }
+impl TimeTableState {
+    /// The position of the selected class among the classes of the week, Monday first.
+    pub fn index(&self) -> Option<usize> {
+        self.index
     }
-}
 
-impl TimeTableState {
     pub fn navigate(&mut self, nav: TimeTableNavigation) {
-        let selected_class: usize;
-        match self.selected_class {
-            Some(n) => selected_class = n,
+        let selected_class: usize = match self.selected_class {
+            Some(n) => n,
             _ => {
                 self.selected_class = Some(0);
                 self.index = Some(0);
                 return;
             }
-        }
+        };
         match nav {
-            TimeTableNavigation::UP => {
+            TimeTableNavigation::Up => {
                 if selected_class == 0 {
-                    self.selected_class = Some(
-                        self.distribution[self.selected_day]
-                            .checked_sub(1)
-                            .unwrap_or(0),
-                    );
+                    self.selected_class =
+                        Some(self.distribution[self.selected_day].saturating_sub(1));
                 } else {
                     self.selected_class = Some(selected_class - 1);
                 }
             }
-            TimeTableNavigation::DOWN => {
-                if selected_class
-                    == self.distribution[self.selected_day]
-                        .checked_sub(1)
-                        .unwrap_or(0)
-                {
+            TimeTableNavigation::Down => {
+                if selected_class == self.distribution[self.selected_day].saturating_sub(1) {
                     self.selected_class = Some(0);
                 } else {
                     self.selected_class = Some(selected_class + 1);
                 }
             }
-            TimeTableNavigation::LEFT => {
+            TimeTableNavigation::Left => {
                 if self.selected_day == 0 {
                     self.selected_day = 4;
                 } else {
@@ -84,7 +72,7 @@ impl TimeTableState {
                 }
                 self.selected_class = Some(0);
             }
-            TimeTableNavigation::RIGHT => {
+            TimeTableNavigation::Right => {
                 if self.selected_day == 4 {
                     self.selected_day = 0;
                 } else {
@@ -94,22 +82,22 @@ impl TimeTableState {
             }
         }
         self.index = Some(
-            self.distribution[..self.selected_day]
-                .into_iter()
-                .sum::<usize>()
+            self.distribution[..self.selected_day].iter().sum::<usize>()
                 + self.selected_class.unwrap_or(0),
         );
     }
 
     pub fn set_distribution(&mut self, tt: &TimeTable) {
-        for i in 0..5 {
+        for i in 0..DAYS {
             self.distribution[i] = tt.classes[i].len();
         }
     }
 }
 
 pub struct TimeTable<'a> {
-    classes: [Vec<&'a NeptunClass>; 5],
+    classes: [Vec<&'a NeptunEvent>; DAYS],
+    all_day: [Vec<&'a AllDayEvent>; DAYS],
+    timezone: Tz,
     style: Style,
     highlight_style: Style,
 }
@@ -117,7 +105,9 @@ pub struct TimeTable<'a> {
 impl<'a> Default for TimeTable<'a> {
     fn default() -> Self {
         Self {
-            classes: [const { Vec::new() }; 5],
+            classes: [const { Vec::new() }; DAYS],
+            all_day: [const { Vec::new() }; DAYS],
+            timezone: DEFAULT_TIMEZONE,
             style: Style::new(),
             highlight_style: Style::new(),
         }
@@ -125,24 +115,26 @@ impl<'a> Default for TimeTable<'a> {
 }
 
 impl<'a> TimeTable<'a> {
-    pub fn from_classes(classes: Vec<&'a NeptunClass>) -> Self {
-        let mut classes_array = [const { Vec::new() }; 5];
-
-        for class in classes {
-            classes_array[class.start.weekday().num_days_from_monday() as usize].push(class);
-        }
-
-        for class_vec in &mut classes_array {
-            class_vec.sort()
-        }
-
+    pub fn from_events(events: Vec<&'a NeptunEvent>, timezone: Tz) -> Self {
         Self {
-            classes: classes_array,
+            classes: weekgrid::columns(events, &timezone),
+            all_day: [const { Vec::new() }; DAYS],
+            timezone,
             style: Style::new(),
             highlight_style: Style::new(),
         }
     }
 
+    /// Adds the date-only events of the week starting on `monday`, drawn as a strip above the
+    /// time axis.
+    pub fn all_day_events(mut self, events: Vec<&'a AllDayEvent>, monday: NaiveDate) -> Self {
+        for (i, day) in self.all_day.iter_mut().enumerate() {
+            let date = monday + TimeDelta::days(i as i64);
+            day.extend(events.iter().filter(|event| event.covers(&date)));
+        }
+        self
+    }
+
     pub fn style<S: Into<Style>>(mut self, style: S) -> Self {
         self.style = style.into();
         self
@@ -153,20 +145,32 @@ impl<'a> TimeTable<'a> {
         self
     }
 
-    fn quarters_from_seven(class: &NeptunClass) -> u8 {
-        let quarters_from_midnight = (class.start.hour() * 60 + class.start.minute()) / 15;
-        quarters_from_midnight as u8 - HOUR_SEVEN_AS_QUARTERS
+    fn quarters_from_twenty(&self, event: &NeptunEvent) -> u8 {
+        let (_, end) = event.span().unwrap_or_default();
+        LAST_QUARTER - weekgrid::quarters(&end, &self.timezone)
     }
 
-    fn quarters_from_twenty(class: &NeptunClass) -> u8 {
-        let quarters_from_midnight = (class.end.hour() * 60 + class.end.minute()) / 15;
-        HOUR_TWENTY_AS_QUARTERS - quarters_from_midnight as u8
+    fn height_in_quarters(&self, event: &NeptunEvent) -> u8 {
+        weekgrid::rows(event, &self.timezone).1
     }
 
-    fn height_in_quarters(class: &NeptunClass) -> u8 {
-        let start_as_quarters = (class.start.hour() * 60 + class.start.minute()) / 15;
-        let end_as_quarters = (class.end.hour() * 60 + class.end.minute()) / 15;
-        (end_as_quarters - start_as_quarters) as u8
+    /// Classes are coloured by class type, every other event by its kind. Cancelled events are
+    /// greyed out.
+    fn event_color(event: &NeptunEvent) -> Color {
+        if event.is_cancelled() {
+            return Color::DarkGray;
+        }
+        match event.kind() {
+            EventKind::Class => match event.class().map(|class| class.class_type()) {
+                Some(ClassType::Seminar) => Color::Blue,
+                Some(ClassType::Lab) => Color::LightMagenta,
+                _ => Color::Cyan,
+            },
+            EventKind::Exam => Color::Red,
+            EventKind::Consultation => Color::Green,
+            EventKind::Deadline => Color::Yellow,
+            EventKind::Other => Color::Gray,
+        }
     }
 }
 
@@ -201,25 +205,48 @@ impl StatefulWidgetRef for TimeTable<'_> {
     type State = TimeTableState;
 
     fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let strip_height = if self.all_day.iter().all(|day| day.is_empty()) {
+            0.0
+        } else {
+            ALL_DAY_STRIP_HEIGHT
+        };
         let canvas = Canvas::default()
             .marker(symbols::Marker::HalfBlock)
             .x_bounds([0.0, 70.0])
-            .y_bounds([0.0, 52.0])
+            .y_bounds([0.0, TIME_AXIS_HEIGHT + strip_height])
             .paint(|ctx| {
+                let mut x_coord = 5.0;
+                for day in &self.all_day {
+                    if let Some(event) = day.first() {
+                        ctx.draw(&Rectangle {
+                            x: x_coord,
+                            y: TIME_AXIS_HEIGHT + 1.0,
+                            height: ALL_DAY_STRIP_HEIGHT - 1.0,
+                            width: 7.0,
+                            color: Color::Yellow,
+                        });
+                        let mut text = event.summary.graphemes(true).take(10).collect::<String>();
+                        if day.len() > 1 {
+                            text.push_str(&format!(" +{}", day.len() - 1));
+                        }
+                        ctx.print(x_coord + 1.0, TIME_AXIS_HEIGHT + 2.0, text);
+                    }
+                    x_coord += 10.0;
+                }
                 let mut x_coord = 5.0;
                 for (i, day) in self.classes.clone().into_iter().enumerate() {
                     for (j, class) in day.into_iter().enumerate() {
-                        let y_coord = f64::from(TimeTable::quarters_from_twenty(&class));
-                        let height = f64::from(TimeTable::height_in_quarters(&class));
+                        let y_coord = f64::from(self.quarters_from_twenty(class));
+                        let height = f64::from(self.height_in_quarters(class));
                         let color: Color;
                         if let Some(n) = state.selected_class {
                             if state.selected_day == i && n == j {
                                 color = Color::White;
                             } else {
-                                color = Color::Cyan;
+                                color = TimeTable::event_color(class);
                             }
                         } else {
-                            color = Color::Cyan;
+                            color = TimeTable::event_color(class);
                         }
 
                         ctx.draw(&Rectangle {
@@ -229,19 +256,24 @@ impl StatefulWidgetRef for TimeTable<'_> {
                             width: 7.0,
                             color,
                         });
-                        let mut text = class.name.graphemes(true).take(10).collect::<String>();
+                        let mut text = class.title().graphemes(true).take(10).collect::<String>();
                         text.push_str("...");
+                        let text = if class.is_cancelled() {
+                            Line::from(text.crossed_out())
+                        } else {
+                            Line::from(text)
+                        };
                         ctx.print(x_coord + 1.0, y_coord + (height / 2.0), text);
                     }
-                    x_coord = x_coord + 10.0;
+                    x_coord += 10.0;
                 }
                 let mut time_iter = NaiveTime::from_hms_opt(20, 0, 0).unwrap();
                 let quarter_delta = TimeDelta::minutes(15);
-                for i in 0..=52 {
+                for i in 0..=QUARTERS {
                     if time_iter.minute() == 0 {
                         ctx.print(0.0, i as f64, time_iter.format("%H:%M").to_string());
                     }
-                    time_iter = time_iter - quarter_delta;
+                    time_iter -= quarter_delta;
                 }
             });
         canvas.render(area, buf);
@@ -251,14 +283,62 @@ impl StatefulWidgetRef for TimeTable<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ratatui::prelude::{Buffer, Rect};
-    use ratatui::widgets::Widget;
+    use crate::exportlanguage::ExportLanguage;
+    use crate::parserules::ParseRules;
+    use chrono::{DateTime, Utc};
+    use icalendar::{Component, EventLike};
 
     #[test]
-    fn butterdog() {
-        let mut buf = Buffer::empty(Rect::new(0, 0, 15, 3));
-        let timetable = TimeTable::default();
-        Widget::render(timetable, Rect::new(0, 0, 15, 3), &mut buf);
-        assert_eq!(true, true);
+    fn places_classes_by_display_time() {
+        let (class, _) = NeptunEvent::parse(
+            &icalendar::Event::new()
+                .summary("Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra")
+                .starts("2025-04-04T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .ends("2025-04-04T09:30:00Z".parse::<DateTime<Utc>>().unwrap())
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        let timetable = TimeTable::from_events(vec![&class], DEFAULT_TIMEZONE);
+
+        assert_eq!(timetable.classes[4].len(), 1);
+        assert_eq!(timetable.quarters_from_twenty(&class), 34);
+        assert_eq!(timetable.height_in_quarters(&class), 6);
+        assert_eq!(TimeTable::event_color(&class), Color::Cyan);
+
+        let (cancelled, _) = NeptunEvent::parse(
+            &icalendar::Event::new()
+                .summary("Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra")
+                .starts("2025-04-11T08:00:00Z".parse::<DateTime<Utc>>().unwrap())
+                .ends("2025-04-11T09:30:00Z".parse::<DateTime<Utc>>().unwrap())
+                .status(icalendar::EventStatus::Cancelled)
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        assert_eq!(TimeTable::event_color(&cancelled), Color::DarkGray);
+    }
+
+    #[test]
+    fn draws_all_day_strip() {
+        let event = AllDayEvent::try_from(
+            &icalendar::Event::new()
+                .summary("Őszi szünet")
+                .all_day(NaiveDate::from_ymd_opt(2024, 10, 28).unwrap())
+                .done(),
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2024, 10, 28).unwrap();
+        let timetable = TimeTable::default().all_day_events(vec![&event], monday);
+        assert_eq!(timetable.all_day[0].len(), 1);
+        assert!(timetable.all_day[1].is_empty());
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, 30));
+        Widget::render(timetable, Rect::new(0, 0, 80, 30), &mut buf);
+        let top_rows = (0..4)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .map(|(x, y)| buf[(x, y)].symbol().to_owned())
+            .collect::<String>();
+        assert!(top_rows.contains("Őszi"));
     }
 }
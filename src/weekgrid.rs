@@ -0,0 +1,89 @@
+use crate::neptunevent::NeptunEvent;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use chrono_tz::Tz;
+
+/// The weekdays of the timetable, Monday to Friday.
+pub const DAYS: usize = 5;
+/// 07:00 and 20:00 in quarter hours since midnight, the first and last line of the time axis.
+pub const FIRST_QUARTER: u8 = 28;
+pub const LAST_QUARTER: u8 = 80;
+/// The height of the time axis in quarter hours.
+pub const QUARTERS: u8 = LAST_QUARTER - FIRST_QUARTER;
+
+/// Quarter hours since local midnight, clamped to the 07:00-20:00 range of the time axis.
+pub fn quarters(time: &DateTime<Utc>, tz: &Tz) -> u8 {
+    let local = time.with_timezone(tz);
+    let quarters_from_midnight = (local.hour() * 60 + local.minute()) / 15;
+    (quarters_from_midnight as u8).clamp(FIRST_QUARTER, LAST_QUARTER)
+}
+
+/// Where an event sits on the time axis: the quarter hours from 07:00 to its start, and the
+/// quarter hours it lasts.
+pub fn rows(event: &NeptunEvent, tz: &Tz) -> (u8, u8) {
+    let (start, end) = event.span().unwrap_or_default();
+    let (start, end) = (quarters(&start, tz), quarters(&end, tz));
+    (start - FIRST_QUARTER, end.saturating_sub(start))
+}
+
+/// The events in a column for each weekday, each in order of start. Weekend events have no
+/// column and are left out.
+pub fn columns<'a>(
+    events: impl IntoIterator<Item = &'a NeptunEvent>,
+    tz: &Tz,
+) -> [Vec<&'a NeptunEvent>; DAYS] {
+    let mut columns: [Vec<&NeptunEvent>; DAYS] = [const { Vec::new() }; DAYS];
+    for event in events {
+        let Some(start) = event.start() else {
+            continue;
+        };
+        let day = start.with_timezone(tz).weekday().num_days_from_monday() as usize;
+        if let Some(column) = columns.get_mut(day) {
+            column.push(event);
+        }
+    }
+    for column in &mut columns {
+        column.sort_by_key(|event| event.start());
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exportlanguage::ExportLanguage;
+    use crate::neptunclass::DEFAULT_TIMEZONE;
+    use crate::parserules::ParseRules;
+    use icalendar::{Component, EventLike};
+
+    fn class(start: &str, end: &str) -> NeptunEvent {
+        let (event, _) = NeptunEvent::parse(
+            &icalendar::Event::new()
+                .summary("Analízis I ( - KMANA1EBNE) - Dr. Kiss Péter - Tanóra")
+                .starts(start.parse::<DateTime<Utc>>().unwrap())
+                .ends(end.parse::<DateTime<Utc>>().unwrap())
+                .done(),
+            ExportLanguage::Hungarian,
+            &ParseRules::builtin(),
+        );
+        event
+    }
+
+    #[test]
+    fn lays_out_a_week() {
+        let friday = class("2025-04-04T08:00:00Z", "2025-04-04T09:30:00Z");
+        let early = class("2025-03-31T04:00:00Z", "2025-03-31T06:00:00Z");
+        let late = class("2025-03-31T17:00:00Z", "2025-03-31T19:00:00Z");
+        let saturday = class("2025-04-05T08:00:00Z", "2025-04-05T09:30:00Z");
+
+        let columns = columns([&friday, &late, &saturday, &early], &DEFAULT_TIMEZONE);
+        assert_eq!(columns[0].len(), 2);
+        assert!(std::ptr::eq(columns[0][0], &early));
+        assert_eq!(columns[4].len(), 1);
+        assert_eq!(columns.iter().map(Vec::len).sum::<usize>(), 3);
+
+        assert_eq!(rows(&friday, &DEFAULT_TIMEZONE), (12, 6));
+        // Clamped to the axis, 06:00-08:00 starts at 07:00 and 19:00-21:00 ends at 20:00.
+        assert_eq!(rows(&early, &DEFAULT_TIMEZONE), (0, 4));
+        assert_eq!(rows(&late, &DEFAULT_TIMEZONE), (48, 4));
+    }
+}